@@ -8,7 +8,6 @@ use lpc8xx_hal::{
     cortex_m_rt::entry,
     nb::block,
     pac::{Interrupt, NVIC},
-    pmu::LowPowerClock,
     prelude::*,
     syscon::WktWakeup,
     usart, CorePeripherals, Peripherals,
@@ -48,10 +47,23 @@ fn main() -> ! {
         usart::Settings::default(),
     );
 
-    let _ = pmu.low_power_clock.enable(&mut pmu.handle);
+    // On LPC82x, the boot ROM can optimize the internal voltage regulation for
+    // low current consumption. The system clock runs at the default 12 MHz.
+    #[cfg(feature = "82x")]
+    {
+        use lpc8xx_hal::{embedded_time::rate::Hertz, pmu::PowerProfile};
 
-    let mut wkt = p.WKT.enable(&mut syscon.handle);
-    wkt.select_clock::<LowPowerClock>();
+        pmu.handle
+            .set_power(PowerProfile::LowCurrent, Hertz(12_000_000))
+            .expect("Setting the power profile shouldn't fail");
+    }
+
+    let low_power_clock = pmu.low_power_clock.enable(&mut pmu.handle);
+
+    let mut wkt = p
+        .WKT
+        .enable(&mut syscon.handle)
+        .select_clock(&low_power_clock);
 
     let five_seconds: u32 = 10_000 * 5;
 
@@ -3,54 +3,63 @@
 
 extern crate panic_rtt_target;
 
-#[rtic::app(device = lpc8xx_hal::pac, peripherals = false)]
+#[rtic::app(
+    device = lpc8xx_hal::pac,
+    peripherals = false,
+    dispatchers = [PIN_INT7_USART4]
+)]
 mod app {
     use lpc8xx_hal::{
-        delay::Delay,
         gpio::{direction::Output, GpioPin, Level},
+        mrt,
         pins::PIO1_1,
-        prelude::*,
         Peripherals,
     };
+    use rtic::time::duration::Milliseconds;
+
+    #[monotonic(binds = MRT0, default = true)]
+    type Mono = mrt::Monotonic<mrt::MRT0, mrt::MRT1, 12_000_000>;
 
     #[shared]
     struct Shared {}
 
     #[local]
     struct Local {
-        delay: Delay,
         led: GpioPin<PIO1_1, Output>,
     }
 
     #[init]
-    fn init(cx: init::Context) -> (Shared, Local, init::Monotonics) {
+    fn init(_: init::Context) -> (Shared, Local, init::Monotonics) {
         rtt_target::rtt_init_print!();
 
         let p = Peripherals::take().unwrap();
 
-        let delay = Delay::new(cx.core.SYST);
-
         let mut syscon = p.SYSCON.split();
         let gpio = p.GPIO.enable(&mut syscon.handle);
+        let mrt = p.MRT0.split(&mut syscon.handle);
+
+        let mono = mrt::Monotonic::new(mrt.mrt0, mrt.mrt1);
 
         let led = p
             .pins
             .pio1_1
             .into_output_pin(gpio.tokens.pio1_1, Level::Low);
 
-        (Shared {}, Local { delay, led }, init::Monotonics())
+        blink::spawn().unwrap();
+
+        (Shared {}, Local { led }, init::Monotonics(mono))
     }
 
-    #[idle(local = [delay, led])]
-    fn idle(cx: idle::Context) -> ! {
-        let delay = cx.local.delay;
+    #[task(local = [led])]
+    fn blink(cx: blink::Context) {
         let led = cx.local.led;
 
-        loop {
-            led.set_high();
-            delay.delay_ms(700_u16);
+        if led.is_set_high() {
             led.set_low();
-            delay.delay_ms(50_u16);
+            blink::spawn_after(Milliseconds(700_u32)).unwrap();
+        } else {
+            led.set_high();
+            blink::spawn_after(Milliseconds(50_u32)).unwrap();
         }
     }
 }
@@ -47,8 +47,8 @@ function build() {
         --verbose \
         --features=$1,no-target-warning$TRYBUILD \
         --target=$HOST_TARGET
-    cargo build --verbose --features=$TARGET-rt,no-target-warning --examples
-    cargo doc --features=$TARGET-rt,no-target-warning
+    cargo build --verbose --features=$TARGET-rt,no-target-warning,rtic-monotonic --examples
+    cargo doc --features=$TARGET-rt,no-target-warning,rtic-monotonic
 
     # Build test suite
     (
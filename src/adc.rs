@@ -31,11 +31,46 @@
 //!     .expect("Read should never fail");
 //! ```
 //!
+//! Sample a value whenever SCT output 3 is set, for example in the middle of a
+//! PWM period:
+//! ``` no_run
+//! use lpc8xx_hal::prelude::*;
+//! use lpc8xx_hal::Peripherals;
+//! use lpc8xx_hal::adc::trigger::{self, Polarity};
+//! use lpc8xx_hal::syscon::clock_source::AdcClock;
+//!
+//! let mut p = Peripherals::take().unwrap();
+//!
+//! let mut syscon = p.SYSCON.split();
+//! let mut swm    = p.SWM.split();
+//!
+//! #[cfg(feature = "82x")]
+//! let mut swm_handle = swm.handle;
+//! #[cfg(feature = "845")]
+//! let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+//!
+//! let adc_clock = AdcClock::new_default();
+//! let mut adc = p.ADC.enable(&adc_clock, &mut syscon.handle);
+//!
+//! let (mut adc_pin, _) = swm
+//!     .fixed_functions
+//!     .adc_0
+//!     .assign(p.pins.pio0_7.into_swm_pin(), &mut swm_handle);
+//!
+//! adc.enable_trigger(&mut adc_pin, &trigger::SctOut3, Polarity::PositiveEdge);
+//!
+//! loop {
+//!     let adc_value = nb::block!(adc.read_triggered())
+//!         .expect("Read should never fail");
+//! }
+//! ```
+//!
 //! Please refer to the [examples in the repository] for more example code.
 //!
 //! [examples in the repository]: https://github.com/lpc-rs/lpc8xx-hal/tree/master/examples
 
 use embedded_hal::adc::{Channel, OneShot};
+use void::Void;
 
 use crate::{
     init_state, pac, swm,
@@ -52,6 +87,9 @@ use crate::{
 /// # `embedded-hal` traits
 /// - [`embedded_hal::adc::OneShot`] for measuring the voltage on a pin
 ///
+/// Conversions can also be started by a hardware trigger. See
+/// [`ADC::enable_trigger`].
+///
 /// [`Peripherals`]: ../struct.Peripherals.html
 /// [module documentation]: index.html
 /// [`embedded_hal::adc::OneShot`]: #impl-OneShot%3CADC%3CEnabled%3C()%3E%3E%2C%20u16%2C%20PIN%3E
@@ -131,6 +169,63 @@ impl ADC<init_state::Enabled> {
             _state: init_state::Disabled,
         }
     }
+
+    /// Start conversions using a hardware trigger
+    ///
+    /// Configures the ADC to convert the voltage on the given pin whenever the
+    /// selected edge occurs on `trigger`. Use [`ADC::read_triggered`] to
+    /// retrieve the results.
+    ///
+    /// The trigger stays enabled until [`ADC::disable_trigger`] is called, or
+    /// a software-triggered conversion is started via the `OneShot`
+    /// implementation.
+    pub fn enable_trigger<PIN, T>(
+        &mut self,
+        _: &mut PIN,
+        _: &T,
+        polarity: trigger::Polarity,
+    ) where
+        PIN: Channel<ADC, ID = u8>,
+        T: trigger::Trigger,
+    {
+        // The trigger should only be changed while the sequence is disabled.
+        self.disable_trigger();
+
+        self.adc.seq_ctrla.write(|w| {
+            unsafe {
+                w.channels().bits(1 << PIN::channel());
+                w.trigger().bits(T::INPUT);
+            }
+            match polarity {
+                trigger::Polarity::NegativeEdge => w.trigpol().negative_edge(),
+                trigger::Polarity::PositiveEdge => w.trigpol().positive_edge(),
+            };
+            w.seq_ena().enabled();
+            w.mode().end_of_conversion()
+        });
+    }
+
+    /// Stop hardware-triggered conversions
+    ///
+    /// Any conversion that is currently in progress will still be completed.
+    pub fn disable_trigger(&mut self) {
+        self.adc.seq_ctrla.modify(|_, w| w.seq_ena().disabled());
+    }
+
+    /// Read the result of a hardware-triggered conversion
+    ///
+    /// Returns `nb::Error::WouldBlock`, if no conversion has completed since
+    /// the last result was read.
+    pub fn read_triggered(&mut self) -> nb::Result<u16, Void> {
+        let read = self.adc.seq_gdata.read();
+
+        if read.datavalid().bit_is_clear() {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        // Returns the result as a 16 bit value
+        Ok(read.result().bits() << 4)
+    }
 }
 
 impl<State> ADC<State> {
@@ -207,3 +302,130 @@ adc_channel!(ADC_8, 8);
 adc_channel!(ADC_9, 9);
 adc_channel!(ADC_10, 10);
 adc_channel!(ADC_11, 11);
+
+/// Contains types that select the hardware trigger of the ADC
+///
+/// See [`ADC::enable_trigger`].
+pub mod trigger {
+    #[cfg(feature = "82x")]
+    use crate::swm;
+
+    /// Implemented for all hardware trigger sources of the ADC
+    pub trait Trigger: private::Sealed {
+        /// The trigger input number, as written to `SEQ_CTRL.TRIGGER`
+        const INPUT: u8;
+    }
+
+    /// The edge of the trigger signal that starts a conversion
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum Polarity {
+        /// Start a conversion on a negative edge
+        NegativeEdge,
+
+        /// Start a conversion on a positive edge
+        PositiveEdge,
+    }
+
+    macro_rules! triggers {
+        ($($(#[$attr:meta])* $name:ident;)*) => {
+            $(
+                $(#[$attr])*
+                pub struct $name;
+            )*
+        };
+    }
+
+    triggers! {
+        /// Pin interrupt 0 (`PININT0`)
+        PinInt0;
+
+        /// Pin interrupt 1 (`PININT1`)
+        PinInt1;
+
+        /// SCT output 3 (`SCT_OUT3`)
+        SctOut3;
+
+        /// SCT output 4 (`SCT_OUT4`)
+        SctOut4;
+
+        /// Analog comparator output
+        AcmpOut;
+
+        /// ARM core TXEV event
+        ArmTxev;
+
+        /// CTIMER match output 3 (`T0_MAT3`)
+        #[cfg(feature = "845")]
+        CtimerMat3;
+
+        /// GPIO pattern match interrupt
+        #[cfg(feature = "845")]
+        GpioIntBmat;
+    }
+
+    macro_rules! impl_trigger {
+        ($($(#[$attr:meta])* $type:ty, $input:expr;)*) => {
+            $(
+                $(#[$attr])*
+                impl private::Sealed for $type {}
+
+                $(#[$attr])*
+                impl Trigger for $type {
+                    const INPUT: u8 = $input;
+                }
+            )*
+        };
+    }
+
+    #[cfg(feature = "82x")]
+    impl_trigger! {
+        PinInt0, 0;
+        PinInt1, 1;
+        SctOut3, 2;
+        SctOut4, 3;
+        AcmpOut, 6;
+        ArmTxev, 7;
+    }
+
+    #[cfg(feature = "845")]
+    impl_trigger! {
+        PinInt0, 1;
+        PinInt1, 2;
+        SctOut3, 3;
+        SctOut4, 4;
+        CtimerMat3, 5;
+        AcmpOut, 6;
+        GpioIntBmat, 7;
+        ArmTxev, 8;
+    }
+
+    #[cfg(feature = "82x")]
+    impl<PIN> private::Sealed
+        for swm::Function<swm::ADC_PINTRIG0, swm::state::Assigned<PIN>>
+    {
+    }
+
+    #[cfg(feature = "82x")]
+    impl<PIN> Trigger
+        for swm::Function<swm::ADC_PINTRIG0, swm::state::Assigned<PIN>>
+    {
+        const INPUT: u8 = 4;
+    }
+
+    #[cfg(feature = "82x")]
+    impl<PIN> private::Sealed
+        for swm::Function<swm::ADC_PINTRIG1, swm::state::Assigned<PIN>>
+    {
+    }
+
+    #[cfg(feature = "82x")]
+    impl<PIN> Trigger
+        for swm::Function<swm::ADC_PINTRIG1, swm::state::Assigned<PIN>>
+    {
+        const INPUT: u8 = 5;
+    }
+
+    mod private {
+        pub trait Sealed {}
+    }
+}
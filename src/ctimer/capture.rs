@@ -0,0 +1,282 @@
+//! Contains types related to CTIMER capture inputs
+//!
+//! Each capture channel copies the value of the timer counter into a capture
+//! register, whenever a selected edge occurs on its input. This can be used to
+//! timestamp external events, or to measure the width of pulses.
+//!
+//! Capture channels are available via the `captures` field of [`Timer`].
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{ctimer::capture::Edge, Peripherals};
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let swm = p.SWM.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+//!
+//! let (input, _) = swm.movable_functions.t0_cap0.assign(
+//!     p.pins.pio0_16.into_swm_pin(),
+//!     &mut swm_handle,
+//! );
+//!
+//! // Count in microseconds
+//! let mut timer = p.CTIMER0.enable_timer::<1_000_000>(&mut syscon.handle);
+//! let mut capture = timer.captures.capture0.attach(input, Edge::Both);
+//!
+//! loop {
+//!     let start = nb::block!(capture.read()).unwrap();
+//!     let end = nb::block!(capture.read()).unwrap();
+//!
+//!     let pulse_width_us = end.wrapping_sub(start);
+//! }
+//! ```
+//!
+//! [`Timer`]: super::Timer
+
+use core::marker::PhantomData;
+
+use cortex_m::interrupt;
+use void::Void;
+
+use crate::{pac::CTIMER0, swm};
+
+use super::channel::state::{Attached, Detached};
+
+/// A CTIMER capture channel
+///
+/// Capture channels start out detached. Use [`Capture::attach`] to assign an
+/// input function and start capturing.
+pub struct Capture<C, State> {
+    channel: PhantomData<C>,
+    _state: PhantomData<State>,
+}
+
+impl<C, State> Capture<C, State> {
+    fn new() -> Self {
+        Self {
+            channel: PhantomData,
+            _state: PhantomData,
+        }
+    }
+}
+
+impl<C> Capture<C, Detached>
+where
+    C: Trait,
+{
+    /// Attach an input function and start capturing
+    ///
+    /// From now on, the counter value is captured on every edge of the
+    /// selected type. The capture interrupt is enabled, which is required for
+    /// [`Capture::read`] to detect new captures. It only reaches the CPU, if
+    /// the CTIMER interrupt has been enabled in the NVIC.
+    pub fn attach<Pin>(
+        self,
+        _: swm::Function<C::Input, swm::state::Assigned<Pin>>,
+        edge: Edge,
+    ) -> Capture<C, Attached> {
+        let mut capture = Capture::new();
+        capture.clear_flag();
+        capture.modify_ccr(edge.bits() | CCR_INTERRUPT);
+        capture
+    }
+}
+
+impl<C> Capture<C, Attached>
+where
+    C: Trait,
+{
+    /// Change the type of edge that triggers a capture
+    pub fn set_edge(&mut self, edge: Edge) {
+        let interrupt =
+            ctimer().ccr.read().bits() >> Self::shift() as u32 & CCR_INTERRUPT;
+        self.modify_ccr(edge.bits() | interrupt);
+    }
+
+    /// Enable the capture interrupt
+    ///
+    /// The capture interrupt is enabled by default. This method only needs to
+    /// be called after the interrupt has been disabled.
+    pub fn enable_interrupt(&mut self) {
+        let edges = ctimer().ccr.read().bits() >> Self::shift() as u32
+            & Edge::Both.bits();
+        self.modify_ccr(edges | CCR_INTERRUPT);
+    }
+
+    /// Disable the capture interrupt
+    ///
+    /// While the interrupt is disabled, the counter value is still captured,
+    /// but [`Capture::read`] won't be able to detect new captures. Use
+    /// [`Capture::value`] to read the captured value instead.
+    pub fn disable_interrupt(&mut self) {
+        let edges = ctimer().ccr.read().bits() >> Self::shift() as u32
+            & Edge::Both.bits();
+        self.modify_ccr(edges);
+        self.clear_flag();
+    }
+
+    /// Stop capturing and detach the channel
+    ///
+    /// The input function stays assigned to its pin.
+    pub fn detach(mut self) -> Capture<C, Detached> {
+        self.modify_ccr(0);
+        self.clear_flag();
+        Capture::new()
+    }
+
+    /// Read a newly captured counter value
+    ///
+    /// Returns the value of the counter at the most recent capture, if a
+    /// capture has happened since the last call to this method. Returns
+    /// `WouldBlock` otherwise.
+    pub fn read(&mut self) -> nb::Result<u32, Void> {
+        if ctimer().ir.read().bits() & Self::flag() == 0 {
+            return Err(nb::Error::WouldBlock);
+        }
+        self.clear_flag();
+
+        Ok(self.value())
+    }
+
+    /// Returns the counter value of the most recent capture
+    ///
+    /// Doesn't check whether a new capture has happened, nor does it affect the
+    /// result of [`Capture::read`].
+    pub fn value(&self) -> u32 {
+        ctimer().cr[C::ID as usize].read().cap().bits()
+    }
+
+    fn clear_flag(&mut self) {
+        // Sound, as writing zeros to the register has no effect.
+        ctimer().ir.write(|w| unsafe { w.bits(Self::flag()) });
+    }
+
+    fn flag() -> u32 {
+        0x1 << (4 + C::ID)
+    }
+}
+
+impl<C, State> Capture<C, State>
+where
+    C: Trait,
+{
+    fn shift() -> u8 {
+        3 * C::ID
+    }
+
+    fn modify_ccr(&mut self, bits: u32) {
+        let shift = Self::shift();
+
+        interrupt::free(|_| {
+            // Sound, as all bits that are written are valid CCR bits.
+            ctimer().ccr.modify(|r, w| unsafe {
+                w.bits(r.bits() & !(0x7 << shift) | bits << shift)
+            });
+        });
+    }
+}
+
+/// The type of edge on a capture input that triggers a capture
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Edge {
+    /// Capture on rising edges
+    Rising,
+
+    /// Capture on falling edges
+    Falling,
+
+    /// Capture on both rising and falling edges
+    Both,
+}
+
+impl Edge {
+    fn bits(self) -> u32 {
+        match self {
+            Edge::Rising => 0b01,
+            Edge::Falling => 0b10,
+            Edge::Both => 0b11,
+        }
+    }
+}
+
+const CCR_INTERRUPT: u32 = 0b100;
+
+/// Contains all CTIMER capture channels
+///
+/// Can be accessed via [`Timer`].
+///
+/// [`Timer`]: super::Timer
+#[allow(missing_docs)]
+pub struct Captures {
+    pub capture0: Capture<Capture0, Detached>,
+    pub capture1: Capture<Capture1, Detached>,
+    pub capture2: Capture<Capture2, Detached>,
+}
+
+impl Captures {
+    pub(super) fn new() -> Self {
+        Self {
+            capture0: Capture::new(),
+            capture1: Capture::new(),
+            capture2: Capture::new(),
+        }
+    }
+}
+
+/// Implemented for all CTIMER capture channels
+pub trait Trait: private::Sealed {
+    /// Identifies the channel
+    const ID: u8;
+
+    /// The SWM function that needs to be assigned to this channel's input pin
+    type Input;
+}
+
+/// Implemented for all SWM functions that are CTIMER capture inputs
+pub trait Input: private::Sealed {
+    /// Identifies the capture channel the input belongs to
+    const ID: u8;
+}
+
+macro_rules! captures {
+    ($($channel:ident, $id:expr, $input:ident;)*) => {
+        $(
+            /// Identifies a CTIMER capture channel
+            pub struct $channel;
+
+            impl private::Sealed for $channel {}
+
+            impl Trait for $channel {
+                const ID: u8 = $id;
+                type Input = swm::$input;
+            }
+
+            impl private::Sealed for swm::$input {}
+
+            impl Input for swm::$input {
+                const ID: u8 = $id;
+            }
+        )*
+    };
+}
+
+captures! {
+    Capture0, 0, T0_CAP0;
+    Capture1, 1, T0_CAP1;
+    Capture2, 2, T0_CAP2;
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+fn ctimer() -> &'static crate::pac::ctimer0::RegisterBlock {
+    // Sound, as capture channels only access their own bits of CCR, IR, and
+    // their own capture register. Modifications of CCR are protected by a
+    // critical section.
+    unsafe { &*CTIMER0::ptr() }
+}
@@ -7,10 +7,7 @@ use embedded_hal_alpha::pwm::PwmPin as PwmPinAlpha;
 
 use crate::{
     init_state::Enabled,
-    pac::{
-        ctimer0::{MR, MSR},
-        CTIMER0,
-    },
+    pac::{ctimer0::MSR, CTIMER0},
     reg_proxy::RegProxy,
 };
 
@@ -18,7 +15,6 @@ use self::state::Attached;
 
 /// A CTIMER PWM channel
 pub struct Channel<T, PeripheralState, State> {
-    mr: RegProxy<MR>,
     msr: RegProxy<MSR>,
     channel: PhantomData<T>,
     peripheral_state: PhantomData<PeripheralState>,
@@ -28,7 +24,6 @@ pub struct Channel<T, PeripheralState, State> {
 impl<T, PeripheralState, State> Channel<T, PeripheralState, State> {
     pub(super) fn new() -> Self {
         Self {
-            mr: RegProxy::new(),
             msr: RegProxy::new(),
             channel: PhantomData,
             peripheral_state: PhantomData,
@@ -60,7 +55,7 @@ where
 
     /// Returns the maximum duty cycle value
     fn get_max_duty(&self) -> Self::Duty {
-        self.mr[3].read().match_().bits()
+        self.msr[3].read().match_shadow().bits()
     }
 
     /// Sets a new duty cycle
@@ -99,7 +94,7 @@ where
 
     /// Returns the maximum duty cycle value
     fn try_get_max_duty(&self) -> Result<Self::Duty, Self::Error> {
-        Ok(self.mr[3].read().match_().bits())
+        Ok(self.msr[3].read().match_shadow().bits())
     }
 
     /// Sets a new duty cycle
@@ -135,5 +130,4 @@ pub(super) mod private {
     pub trait Sealed {}
 }
 
-reg!(MR, [MR; 4], CTIMER0, mr);
 reg!(MSR, [MSR; 4], CTIMER0, msr);
@@ -0,0 +1,193 @@
+use core::marker::PhantomData;
+
+use crate::{
+    init_state::Disabled,
+    pac::{Interrupt, CTIMER0, NVIC},
+    syscon,
+};
+
+use super::{
+    capture::{self, Edge},
+    channel::state::Detached,
+    peripheral::{self, CTIMER},
+};
+
+/// The CTIMER, operating as a counter of external edges
+///
+/// Use [`CTIMER::enable_counter`] to gain access to an instance of this struct.
+///
+/// The counter is incremented on each selected edge of the capture input `I`,
+/// instead of the system clock. The input is sampled by the system clock, so
+/// the frequency of the counted signal must be less than half the system clock
+/// frequency.
+///
+/// Match register 0 can be used to get notified, once a specific count has
+/// been reached. Its interrupt can be routed to the CPU using
+/// [`Counter::enable_in_nvic`].
+///
+/// # Example
+///
+/// ``` no_run
+/// use lpc8xx_hal::{ctimer::capture::Edge, Peripherals};
+///
+/// let p = Peripherals::take().unwrap();
+///
+/// let swm = p.SWM.split();
+/// let mut syscon = p.SYSCON.split();
+///
+/// let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+///
+/// let (input, _) = swm.movable_functions.t0_cap1.assign(
+///     p.pins.pio0_17.into_swm_pin(),
+///     &mut swm_handle,
+/// );
+///
+/// let mut counter =
+///     p.CTIMER0.enable_counter(input, Edge::Rising, &mut syscon.handle);
+///
+/// // Get notified after 1000 pulses
+/// counter.enable_match(1000);
+/// while !counter.match_occurred() {}
+///
+/// let pulses = counter.count();
+/// ```
+pub struct Counter<I> {
+    inner: CTIMER0,
+    clock: u32,
+    _input: PhantomData<I>,
+}
+
+impl<I> Counter<I>
+where
+    I: capture::Input,
+{
+    pub(super) fn new(
+        inner: CTIMER0,
+        clock: u32,
+        edge: Edge,
+        syscon: &mut syscon::Handle,
+    ) -> Self {
+        peripheral::enable(&inner, syscon);
+
+        inner.ctcr.write(|w| {
+            match edge {
+                Edge::Rising => w.ctmode().counter_rising_edge(),
+                Edge::Falling => w.ctmode().counter_falling_edge(),
+                Edge::Both => w.ctmode().counter_dual_edge(),
+            };
+            w.cinsel().bits(I::ID)
+        });
+
+        // Reset the counter and start it.
+        inner.tcr.write(|w| w.crst().enabled());
+        inner.tcr.write(|w| w.cen().set_bit());
+
+        Self {
+            inner,
+            clock,
+            _input: PhantomData,
+        }
+    }
+
+    /// Returns the number of edges counted
+    ///
+    /// The count wraps around once it reaches the maximum value of `u32`.
+    pub fn count(&self) -> u32 {
+        self.inner.tc.read().tcval().bits()
+    }
+
+    /// Reset the count to zero
+    pub fn reset(&mut self) {
+        self.inner.tcr.modify(|_, w| w.crst().enabled());
+        self.inner.tcr.modify(|_, w| w.crst().disabled());
+    }
+
+    /// Enable the match interrupt for the given count
+    ///
+    /// Once the count reaches `count`, the match interrupt flag is set. Use
+    /// [`Counter::match_occurred`] to check it.
+    pub fn enable_match(&mut self, count: u32) {
+        self.inner.mr[0].write(|w| unsafe { w.match_().bits(count) });
+        self.inner.ir.write(|w| w.mr0int().set_bit());
+        self.inner.mcr.modify(|_, w| w.mr0i().set_bit());
+    }
+
+    /// Disable the match interrupt
+    pub fn disable_match(&mut self) {
+        self.inner.mcr.modify(|_, w| w.mr0i().clear_bit());
+        self.inner.ir.write(|w| w.mr0int().set_bit());
+    }
+
+    /// Indicates whether the count set with [`Counter::enable_match`] has been
+    /// reached
+    ///
+    /// Clears the match interrupt flag, if it was set.
+    pub fn match_occurred(&mut self) -> bool {
+        if self.inner.ir.read().mr0int().bit_is_clear() {
+            return false;
+        }
+        self.inner.ir.write(|w| w.mr0int().set_bit());
+
+        true
+    }
+
+    /// Enable the CTIMER interrupt in the NVIC
+    ///
+    /// This only enables the interrupt in the NVIC. The match interrupt itself
+    /// is enabled using [`Counter::enable_match`].
+    pub fn enable_in_nvic(&mut self) {
+        // Safe, because there's no critical section here that this could
+        // interfere with.
+        unsafe { NVIC::unmask(Interrupt::CTIMER0) };
+    }
+
+    /// Disable the CTIMER interrupt in the NVIC
+    ///
+    /// This only disables the interrupt in the NVIC. It doesn't change anything
+    /// about the interrupt configuration within the CTIMER.
+    pub fn disable_in_nvic(&mut self) {
+        NVIC::mask(Interrupt::CTIMER0);
+    }
+
+    /// Clear the CTIMER's interrupt pending flag in the NVIC
+    ///
+    /// This only clears the interrupt's pending flag in the NVIC. It does not
+    /// affect any of the interrupt-related flags in the peripheral.
+    pub fn clear_nvic_pending(&mut self) {
+        NVIC::unpend(Interrupt::CTIMER0);
+    }
+
+    /// Disable the CTIMER
+    ///
+    /// Consumes this instance of `Counter` and returns a disabled `CTIMER`,
+    /// which can be enabled again in any mode.
+    pub fn disable(
+        self,
+        syscon: &mut syscon::Handle,
+    ) -> CTIMER<Disabled, Detached, Detached, Detached> {
+        self.inner.tcr.write(|w| w.cen().clear_bit());
+        self.inner.ctcr.reset();
+        self.inner.mcr.reset();
+        self.inner.ir.write(|w| w.mr0int().set_bit());
+
+        syscon.disable_clock(&self.inner);
+
+        CTIMER::disabled(self.inner, self.clock)
+    }
+
+    /// Return the raw peripheral
+    ///
+    /// This method serves as an escape hatch from the HAL API. It returns the
+    /// raw peripheral, allowing you to do whatever you want with it, without
+    /// limitations imposed by the API.
+    ///
+    /// If you are using this method because a feature you need is missing from
+    /// the HAL API, please [open an issue] or, if an issue for your feature
+    /// request already exists, comment on the existing issue, so we can
+    /// prioritize it accordingly.
+    ///
+    /// [open an issue]: https://github.com/lpc-rs/lpc8xx-hal/issues
+    pub fn free(self) -> CTIMER0 {
+        self.inner
+    }
+}
@@ -1,6 +1,14 @@
 //! API for the CTIMER peripheral
 //!
-//! Currently, only PWM output functionality is implemented.
+//! The CTIMER can be used to generate PWM signals, or as a general-purpose
+//! 32-bit timer (see [`CTIMER::enable_timer`]). In timer mode, external edges
+//! can be timestamped using the [`capture`] channels. It can also count edges
+//! on a capture input (see [`CTIMER::enable_counter`]), or set, clear, and
+//! toggle its match outputs in [`waveform`] mode.
+//!
+//! PWM channels can be attached in order, using [`CTIMER::attach`], or
+//! individually, using methods like [`CTIMER::attach_channel2`], which allows
+//! using any subset of the channels.
 //!
 //! # Example
 //!
@@ -42,13 +50,29 @@
 //! }
 //! ```
 
+pub mod capture;
 pub mod channel;
+pub mod waveform;
 
+mod counter;
 mod gen;
 mod peripheral;
+mod timer;
+
+/// The default frequency of the clock that drives the CTIMER
+///
+/// This is the frequency of the system clock after reset. It can be overridden
+/// using [`CTIMER::set_clock_frequency`].
+const INPUT_CLOCK: u32 = 12_000_000;
 
 pub use self::{
     channel::Channel,
+    counter::Counter,
     gen::*,
-    peripheral::{Channels1, Channels12, Channels123, CTIMER},
+    peripheral::{
+        Channels1, Channels12, Channels123, Channels13, Channels2, Channels23,
+        Channels3, CTIMER,
+    },
+    timer::Timer,
+    waveform::WaveformTimer,
 };
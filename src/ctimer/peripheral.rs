@@ -1,7 +1,10 @@
 use core::convert::Infallible;
 
+use cortex_m::interrupt;
+
 use embedded_hal::{Pwm, PwmPin as _};
 use embedded_hal_alpha::pwm::{Pwm as PwmAlpha, PwmPin as _};
+use embedded_time::rate::Hertz;
 
 use crate::{
     init_state::{Disabled, Enabled},
@@ -10,11 +13,16 @@ use crate::{
 };
 
 use super::{
+    capture::{self, Edge},
     channel::{
         self,
         state::{Attached, Detached},
     },
+    counter::Counter,
     gen::{Channel1, Channel2, Channel3, Channels},
+    timer::Timer,
+    waveform::{MatchOutputs, WaveformTimer},
+    INPUT_CLOCK,
 };
 
 /// Interface to a CTimer peripheral
@@ -32,44 +40,124 @@ pub struct CTIMER<State, Channel1State, Channel2State, Channel3State> {
 
     inner: CTIMER0,
     state: State,
+    clock: u32,
 }
 
 impl CTIMER<Disabled, Detached, Detached, Detached> {
     pub(crate) fn new(ct: CTIMER0) -> Self {
+        Self::disabled(ct, INPUT_CLOCK)
+    }
+
+    pub(super) fn disabled(ct: CTIMER0, clock: u32) -> Self {
         Self {
             channels: Channels::new(),
             inner: ct,
             state: Disabled,
+            clock,
         }
     }
+
+    /// Enable the CTIMER as a general-purpose timer
+    ///
+    /// The timer counts at a rate of `HZ` ticks per second. Please refer to
+    /// [`Timer`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `HZ` is zero, larger than the CTIMER clock frequency, or
+    /// doesn't divide it evenly. See [`CTIMER::set_clock_frequency`].
+    pub fn enable_timer<const HZ: u32>(
+        self,
+        syscon: &mut syscon::Handle,
+    ) -> Timer<HZ> {
+        Timer::new(self.inner, self.clock, syscon)
+    }
+
+    /// Enable the CTIMER in waveform mode
+    ///
+    /// The counter counts at a rate of `HZ` ticks per second, and the match
+    /// outputs can be set, cleared, or toggled on match. Please refer to the
+    /// [`waveform`] module for details. The match outputs are returned along
+    /// with the timer.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `HZ` is zero, larger than the CTIMER clock frequency, or
+    /// doesn't divide it evenly. See [`CTIMER::set_clock_frequency`].
+    ///
+    /// [`waveform`]: super::waveform
+    pub fn enable_waveform<const HZ: u32>(
+        self,
+        syscon: &mut syscon::Handle,
+    ) -> (WaveformTimer<HZ>, MatchOutputs) {
+        WaveformTimer::new(self.inner, self.clock, syscon)
+    }
+
+    /// Enable the CTIMER as a counter of external edges
+    ///
+    /// The counter is incremented on each edge of the given type on the
+    /// capture input. Please refer to [`Counter`] for details.
+    pub fn enable_counter<I, Pin>(
+        self,
+        _: swm::Function<I, swm::state::Assigned<Pin>>,
+        edge: Edge,
+        syscon: &mut syscon::Handle,
+    ) -> Counter<I>
+    where
+        I: capture::Input,
+    {
+        Counter::new(self.inner, self.clock, edge, syscon)
+    }
 }
 
 impl<Channel1State, Channel2State, Channel3State>
     CTIMER<Disabled, Channel1State, Channel2State, Channel3State>
 {
+    /// Declare the frequency of the clock that drives the CTIMER
+    ///
+    /// The CTIMER runs on the system clock. Unless told otherwise, it assumes
+    /// the system clock runs at 12 MHz, its frequency after reset. If you
+    /// configure a different system clock, declare its frequency here, before
+    /// enabling the CTIMER. The frequency is used to compute the prescaler of
+    /// the timer modes, and the PWM frequency.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `frequency` is zero.
+    pub fn set_clock_frequency(&mut self, frequency: impl Into<Hertz>) {
+        let frequency = frequency.into().0;
+        assert!(frequency != 0);
+        self.clock = frequency;
+    }
+
     /// Start the PWM timer, with a predefined period and prescaler
     ///
     /// The `period` sets resolution of the pwm and is returned with
     /// `get_max_duty`.
+    ///
+    /// Once enabled, the PWM frequency can be changed at runtime, using
+    /// [`CTIMER::set_frequency`].
     pub fn enable(
         self,
         period: u32,
         prescaler: u32,
         syscon: &mut syscon::Handle,
     ) -> CTIMER<Enabled, Channel1State, Channel2State, Channel3State> {
-        syscon.enable_clock(&self.inner);
+        enable(&self.inner, syscon);
 
         let mut self_ = CTIMER {
             channels: Channels::new(),
             inner: self.inner,
             state: Enabled(()),
+            clock: self.clock,
         };
 
         unsafe { self_.inner.pr.write(|w| w.prval().bits(prescaler)) };
         self_.set_period(period);
         self_.inner.mcr.write(|w| {
             w.mr3r().set_bit();
-            // Use shadow registers for the pwm output matches
+            // Use shadow registers for the period and the pwm output matches
+            w.mr3rl().set_bit();
             w.mr0rl().set_bit();
             w.mr1rl().set_bit();
             w.mr2rl().set_bit()
@@ -100,10 +188,13 @@ impl CTIMER<Enabled, Detached, Detached, Detached> {
             swm::state::Assigned<Pin>,
         >,
     ) -> CTIMER<Enabled, Attached, Detached, Detached> {
+        self.inner.pwmc.modify(|_, w| w.pwmen0().set_bit());
+
         CTIMER {
             channels: Channels::new(),
             inner: self.inner,
             state: self.state,
+            clock: self.clock,
         }
     }
 }
@@ -120,10 +211,13 @@ impl CTIMER<Enabled, Attached, Detached, Detached> {
             swm::state::Assigned<Pin>,
         >,
     ) -> CTIMER<Enabled, Attached, Attached, Detached> {
+        self.inner.pwmc.modify(|_, w| w.pwmen1().set_bit());
+
         CTIMER {
             channels: Channels::new(),
             inner: self.inner,
             state: self.state,
+            clock: self.clock,
         }
     }
 }
@@ -141,10 +235,166 @@ impl CTIMER<Enabled, Attached, Attached, Detached> {
             swm::state::Assigned<Pin>,
         >,
     ) -> CTIMER<Enabled, Attached, Attached, Attached> {
+        self.inner.pwmc.modify(|_, w| w.pwmen2().set_bit());
+
+        CTIMER {
+            channels: Channels::new(),
+            inner: self.inner,
+            state: self.state,
+            clock: self.clock,
+        }
+    }
+}
+
+impl<Channel2State, Channel3State>
+    CTIMER<Enabled, Detached, Channel2State, Channel3State>
+{
+    /// Attach an output function to channel 1
+    ///
+    /// Unlike [`CTIMER::attach`], this method is available regardless of which
+    /// other channels are attached.
+    pub fn attach_channel1<Pin>(
+        self,
+        _: swm::Function<
+            <Channel1 as channel::Trait>::Output,
+            swm::state::Assigned<Pin>,
+        >,
+    ) -> CTIMER<Enabled, Attached, Channel2State, Channel3State> {
+        self.inner.pwmc.modify(|_, w| w.pwmen0().set_bit());
+
+        CTIMER {
+            channels: Channels::new(),
+            inner: self.inner,
+            state: self.state,
+            clock: self.clock,
+        }
+    }
+}
+
+impl<Channel1State, Channel3State>
+    CTIMER<Enabled, Channel1State, Detached, Channel3State>
+{
+    /// Attach an output function to channel 2
+    ///
+    /// Unlike [`CTIMER::attach`], this method is available regardless of which
+    /// other channels are attached.
+    pub fn attach_channel2<Pin>(
+        self,
+        _: swm::Function<
+            <Channel2 as channel::Trait>::Output,
+            swm::state::Assigned<Pin>,
+        >,
+    ) -> CTIMER<Enabled, Channel1State, Attached, Channel3State> {
+        self.inner.pwmc.modify(|_, w| w.pwmen1().set_bit());
+
+        CTIMER {
+            channels: Channels::new(),
+            inner: self.inner,
+            state: self.state,
+            clock: self.clock,
+        }
+    }
+}
+
+impl<Channel1State, Channel2State>
+    CTIMER<Enabled, Channel1State, Channel2State, Detached>
+{
+    /// Attach an output function to channel 3
+    ///
+    /// Unlike [`CTIMER::attach`], this method is available regardless of which
+    /// other channels are attached.
+    pub fn attach_channel3<Pin>(
+        self,
+        _: swm::Function<
+            <Channel3 as channel::Trait>::Output,
+            swm::state::Assigned<Pin>,
+        >,
+    ) -> CTIMER<Enabled, Channel1State, Channel2State, Attached> {
+        self.inner.pwmc.modify(|_, w| w.pwmen2().set_bit());
+
+        CTIMER {
+            channels: Channels::new(),
+            inner: self.inner,
+            state: self.state,
+            clock: self.clock,
+        }
+    }
+}
+
+impl<Channel2State, Channel3State>
+    CTIMER<Enabled, Attached, Channel2State, Channel3State>
+{
+    /// Detach channel 1
+    ///
+    /// Stops PWM output on channel 1. The output function stays assigned to its
+    /// pin, which is driven low from now on.
+    pub fn detach_channel1(
+        self,
+    ) -> CTIMER<Enabled, Detached, Channel2State, Channel3State> {
+        self.inner.pwmc.modify(|_, w| w.pwmen0().clear_bit());
+        // Without PWM mode, the output follows the external match bit.
+        self.inner.emr.modify(|_, w| {
+            w.em0().clear_bit();
+            w.emc0().do_nothing()
+        });
+
         CTIMER {
             channels: Channels::new(),
             inner: self.inner,
             state: self.state,
+            clock: self.clock,
+        }
+    }
+}
+
+impl<Channel1State, Channel3State>
+    CTIMER<Enabled, Channel1State, Attached, Channel3State>
+{
+    /// Detach channel 2
+    ///
+    /// Stops PWM output on channel 2. The output function stays assigned to its
+    /// pin, which is driven low from now on.
+    pub fn detach_channel2(
+        self,
+    ) -> CTIMER<Enabled, Channel1State, Detached, Channel3State> {
+        self.inner.pwmc.modify(|_, w| w.pwmen1().clear_bit());
+        // Without PWM mode, the output follows the external match bit.
+        self.inner.emr.modify(|_, w| {
+            w.em1().clear_bit();
+            w.emc1().do_nothing()
+        });
+
+        CTIMER {
+            channels: Channels::new(),
+            inner: self.inner,
+            state: self.state,
+            clock: self.clock,
+        }
+    }
+}
+
+impl<Channel1State, Channel2State>
+    CTIMER<Enabled, Channel1State, Channel2State, Attached>
+{
+    /// Detach channel 3
+    ///
+    /// Stops PWM output on channel 3. The output function stays assigned to its
+    /// pin, which is driven low from now on.
+    pub fn detach_channel3(
+        self,
+    ) -> CTIMER<Enabled, Channel1State, Channel2State, Detached> {
+        self.inner.pwmc.modify(|_, w| w.pwmen2().clear_bit());
+        // Without PWM mode, the output follows the external match bit.
+        self.inner.emr.modify(|_, w| {
+            w.em2().clear_bit();
+            w.emc2().do_nothing()
+        });
+
+        CTIMER {
+            channels: Channels::new(),
+            inner: self.inner,
+            state: self.state,
+            clock: self.clock,
         }
     }
 }
@@ -173,13 +423,88 @@ impl<Channel1State, Channel2State, Channel3State>
             channels: Channels::new(),
             inner: self.inner,
             state: Disabled,
+            clock: self.clock,
+        }
+    }
+
+    /// Returns the PWM frequency
+    ///
+    /// The frequency is computed from the CTIMER clock frequency (see
+    /// [`CTIMER::set_clock_frequency`]). If the frequency has been changed
+    /// recently, this returns the new frequency, even if the current PWM period
+    /// hasn't finished yet.
+    pub fn frequency(&self) -> Hertz {
+        let prescaler = self.inner.pr.read().prval().bits();
+        let ticks = (prescaler as u64 + 1) * (self.get_period() as u64 + 1);
+        Hertz((self.clock as u64 / ticks) as u32)
+    }
+
+    /// Change the PWM frequency
+    ///
+    /// Computes the period from the given frequency, the CTIMER clock frequency
+    /// (see [`CTIMER::set_clock_frequency`]), and the prescaler that was passed
+    /// to [`CTIMER::enable`]. Updates the duty cycles of all channels, so their
+    /// duty ratio stays the same.
+    ///
+    /// The new frequency and duty cycles take effect together at the end of a
+    /// PWM period, so the change doesn't cause any glitches on the outputs. To
+    /// make sure of that, all of them are written during the first half of a
+    /// period, with interrupts disabled, which means this method may block for
+    /// up to half a PWM period. This only works, if half a period is longer
+    /// than the few instructions required to write the new values, which is
+    /// the case for any practical PWM frequency.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `frequency` is zero, or too high to be generated with the
+    /// current prescaler.
+    pub fn set_frequency(&mut self, frequency: impl Into<Hertz>) {
+        let frequency = frequency.into().0;
+        let prescaler = self.inner.pr.read().prval().bits();
+
+        let counter_clock = self.clock / (prescaler + 1);
+        assert!(frequency != 0 && frequency <= counter_clock);
+        let period = counter_clock / frequency - 1;
+
+        let old_period = self.get_period() as u64 + 1;
+        let mut duties = [0; 3];
+        for (duty, msr) in duties.iter_mut().zip(&self.inner.msr[..3]) {
+            let old_duty = msr.read().match_shadow().bits() as u64;
+            *duty = (old_duty * (period as u64 + 1) / old_period) as u32;
+        }
+
+        // All match registers are reloaded from their shadow registers at the
+        // end of every period. If a period ended while we're writing the shadow
+        // registers, the outputs would run for one period with a mix of old and
+        // new values. Prevent that by only writing while the current period has
+        // at least half of its length left.
+        let inner = &self.inner;
+        let half_period = inner.mr[3].read().match_().bits() / 2;
+        loop {
+            let written = interrupt::free(|_| {
+                if inner.tc.read().tcval().bits() > half_period {
+                    return false;
+                }
+
+                for (duty, msr) in duties.iter().zip(&inner.msr[..3]) {
+                    msr.write(|w| unsafe { w.match_shadow().bits(*duty) });
+                }
+                inner.msr[3]
+                    .write(|w| unsafe { w.match_shadow().bits(period) });
+
+                true
+            });
+
+            if written {
+                break;
+            }
         }
     }
 
     // Private methods
 
     fn get_period(&self) -> u32 {
-        self.inner.mr[3].read().match_().bits()
+        self.inner.msr[3].read().match_shadow().bits()
     }
 
     fn get_max_duty(&self) -> u32 {
@@ -187,8 +512,12 @@ impl<Channel1State, Channel2State, Channel3State>
     }
 
     fn set_period(&mut self, period: u32) {
-        // Use MAT3 to reset the counter
-        unsafe { self.inner.mr[3].write(|w| w.match_().bits(period)) };
+        // Use MAT3 to reset the counter. The shadow register is reloaded into
+        // the match register on each reset, so it needs to be written too.
+        unsafe {
+            self.inner.mr[3].write(|w| w.match_().bits(period));
+            self.inner.msr[3].write(|w| w.match_shadow().bits(period));
+        }
 
         // Reset counter. Otherwise we can run into the case where the counter
         // is already larger than period, and won't be reset until it wrapped.
@@ -217,352 +546,179 @@ impl<State, Channel1State, Channel2State, Channel3State>
     }
 }
 
-impl Pwm for CTIMER<Enabled, Attached, Detached, Detached> {
-    type Channel = Channels1;
-    type Time = u32;
-    type Duty = u32;
-
-    fn disable(&mut self, channel: Self::Channel) {
-        match channel {
-            Self::Channel::Channel1 => self.channels.channel1.disable(),
-        }
-    }
-
-    fn enable(&mut self, channel: Self::Channel) {
-        match channel {
-            Self::Channel::Channel1 => self.channels.channel1.enable(),
-        }
-    }
-
-    fn get_period(&self) -> Self::Time {
-        self.get_period()
-    }
-
-    fn get_duty(&self, channel: Self::Channel) -> Self::Duty {
-        match channel {
-            Self::Channel::Channel1 => self.channels.channel1.get_duty(),
-        }
-    }
-
-    fn get_max_duty(&self) -> Self::Duty {
-        self.get_max_duty()
-    }
-
-    fn set_duty(&mut self, channel: Self::Channel, duty: Self::Duty) {
-        match channel {
-            Self::Channel::Channel1 => self.channels.channel1.set_duty(duty),
-        }
-    }
-
-    fn set_period<P>(&mut self, period: P)
-    where
-        P: Into<Self::Time>,
-    {
-        self.set_period(period.into())
-    }
-}
-
-impl Pwm for CTIMER<Enabled, Attached, Attached, Detached> {
-    type Channel = Channels12;
-    type Time = u32;
-    type Duty = u32;
-
-    fn disable(&mut self, channel: Self::Channel) {
-        match channel {
-            Self::Channel::Channel1 => self.channels.channel1.disable(),
-            Self::Channel::Channel2 => self.channels.channel2.disable(),
-        }
-    }
-
-    fn enable(&mut self, channel: Self::Channel) {
-        match channel {
-            Self::Channel::Channel1 => self.channels.channel1.enable(),
-            Self::Channel::Channel2 => self.channels.channel2.enable(),
-        }
-    }
-
-    fn get_period(&self) -> Self::Time {
-        self.get_period()
-    }
-
-    fn get_duty(&self, channel: Self::Channel) -> Self::Duty {
-        match channel {
-            Self::Channel::Channel1 => self.channels.channel1.get_duty(),
-            Self::Channel::Channel2 => self.channels.channel2.get_duty(),
-        }
-    }
-
-    fn get_max_duty(&self) -> Self::Duty {
-        self.get_max_duty()
-    }
-
-    fn set_duty(&mut self, channel: Self::Channel, duty: Self::Duty) {
-        match channel {
-            Self::Channel::Channel1 => self.channels.channel1.set_duty(duty),
-            Self::Channel::Channel2 => self.channels.channel2.set_duty(duty),
-        }
-    }
-
-    fn set_period<P>(&mut self, period: P)
-    where
-        P: Into<Self::Time>,
-    {
-        self.set_period(period.into())
-    }
-}
-
-impl Pwm for CTIMER<Enabled, Attached, Attached, Attached> {
-    type Channel = Channels123;
-    type Time = u32;
-    type Duty = u32;
-
-    fn disable(&mut self, channel: Self::Channel) {
-        match channel {
-            Self::Channel::Channel1 => self.channels.channel1.disable(),
-            Self::Channel::Channel2 => self.channels.channel2.disable(),
-            Self::Channel::Channel3 => self.channels.channel3.disable(),
-        }
-    }
-
-    fn enable(&mut self, channel: Self::Channel) {
-        match channel {
-            Self::Channel::Channel1 => self.channels.channel1.enable(),
-            Self::Channel::Channel2 => self.channels.channel2.enable(),
-            Self::Channel::Channel3 => self.channels.channel3.enable(),
-        }
-    }
-
-    fn get_period(&self) -> Self::Time {
-        self.get_period()
-    }
-
-    fn get_duty(&self, channel: Self::Channel) -> Self::Duty {
-        match channel {
-            Self::Channel::Channel1 => self.channels.channel1.get_duty(),
-            Self::Channel::Channel2 => self.channels.channel2.get_duty(),
-            Self::Channel::Channel3 => self.channels.channel3.get_duty(),
-        }
-    }
-
-    fn get_max_duty(&self) -> Self::Duty {
-        self.get_max_duty()
-    }
-
-    fn set_duty(&mut self, channel: Self::Channel, duty: Self::Duty) {
-        match channel {
-            Self::Channel::Channel1 => self.channels.channel1.set_duty(duty),
-            Self::Channel::Channel2 => self.channels.channel2.set_duty(duty),
-            Self::Channel::Channel3 => self.channels.channel3.set_duty(duty),
-        }
-    }
-
-    fn set_period<P>(&mut self, period: P)
-    where
-        P: Into<Self::Time>,
-    {
-        self.set_period(period.into())
-    }
+/// Enable the CTIMER clock and reset the peripheral
+///
+/// Every mode starts from the reset state, so no configuration of a mode the
+/// CTIMER was previously used in (like match actions or a counter reset on
+/// match 3) can carry over, even if it was disabled without cleaning up.
+pub(super) fn enable(inner: &CTIMER0, syscon: &mut syscon::Handle) {
+    syscon.enable_clock(inner);
+    syscon.assert_reset(inner);
+    syscon.clear_reset(inner);
 }
 
-impl PwmAlpha for CTIMER<Enabled, Attached, Detached, Detached> {
-    type Error = Infallible;
-    type Channel = Channels1;
-    type Time = u32;
-    type Duty = u32;
-
-    fn try_disable(
-        &mut self,
-        channel: Self::Channel,
-    ) -> Result<(), Self::Error> {
-        match channel {
-            Self::Channel::Channel1 => self.channels.channel1.try_disable(),
-        }
-    }
-
-    fn try_enable(
-        &mut self,
-        channel: Self::Channel,
-    ) -> Result<(), Self::Error> {
-        match channel {
-            Self::Channel::Channel1 => self.channels.channel1.try_enable(),
-        }
-    }
-
-    fn try_get_period(&self) -> Result<Self::Time, Self::Error> {
-        Ok(self.get_period())
-    }
-
-    fn try_get_duty(
-        &self,
-        channel: Self::Channel,
-    ) -> Result<Self::Duty, Self::Error> {
-        match channel {
-            Self::Channel::Channel1 => self.channels.channel1.try_get_duty(),
-        }
-    }
-
-    fn try_get_max_duty(&self) -> Result<Self::Duty, Self::Error> {
-        Ok(self.get_max_duty())
-    }
-
-    fn try_set_duty(
-        &mut self,
-        channel: Self::Channel,
-        duty: Self::Duty,
-    ) -> Result<(), Self::Error> {
-        match channel {
-            Self::Channel::Channel1 => {
-                self.channels.channel1.try_set_duty(duty)
+macro_rules! pwm {
+    (
+        $(
+            $channels:ident,
+            ($state1:ident, $state2:ident, $state3:ident),
+            [$($channel:ident: $field:ident),*];
+        )*
+    ) => {
+        $(
+            impl Pwm for CTIMER<Enabled, $state1, $state2, $state3> {
+                type Channel = $channels;
+                type Time = u32;
+                type Duty = u32;
+
+                fn disable(&mut self, channel: Self::Channel) {
+                    match channel {
+                        $(
+                            $channels::$channel =>
+                                self.channels.$field.disable(),
+                        )*
+                    }
+                }
+
+                fn enable(&mut self, channel: Self::Channel) {
+                    match channel {
+                        $(
+                            $channels::$channel =>
+                                self.channels.$field.enable(),
+                        )*
+                    }
+                }
+
+                fn get_period(&self) -> Self::Time {
+                    self.get_period()
+                }
+
+                fn get_duty(&self, channel: Self::Channel) -> Self::Duty {
+                    match channel {
+                        $(
+                            $channels::$channel =>
+                                self.channels.$field.get_duty(),
+                        )*
+                    }
+                }
+
+                fn get_max_duty(&self) -> Self::Duty {
+                    self.get_max_duty()
+                }
+
+                fn set_duty(
+                    &mut self,
+                    channel: Self::Channel,
+                    duty: Self::Duty,
+                ) {
+                    match channel {
+                        $(
+                            $channels::$channel =>
+                                self.channels.$field.set_duty(duty),
+                        )*
+                    }
+                }
+
+                fn set_period<P>(&mut self, period: P)
+                where
+                    P: Into<Self::Time>,
+                {
+                    self.set_period(period.into())
+                }
             }
-        }
-    }
 
-    fn try_set_period<P>(&mut self, period: P) -> Result<(), Self::Error>
-    where
-        P: Into<Self::Time>,
-    {
-        Ok(self.set_period(period.into()))
-    }
-}
-
-impl PwmAlpha for CTIMER<Enabled, Attached, Attached, Detached> {
-    type Error = Infallible;
-    type Channel = Channels12;
-    type Time = u32;
-    type Duty = u32;
-
-    fn try_disable(
-        &mut self,
-        channel: Self::Channel,
-    ) -> Result<(), Self::Error> {
-        match channel {
-            Self::Channel::Channel1 => self.channels.channel1.try_disable(),
-            Self::Channel::Channel2 => self.channels.channel2.try_disable(),
-        }
-    }
-
-    fn try_enable(
-        &mut self,
-        channel: Self::Channel,
-    ) -> Result<(), Self::Error> {
-        match channel {
-            Self::Channel::Channel1 => self.channels.channel1.try_enable(),
-            Self::Channel::Channel2 => self.channels.channel2.try_enable(),
-        }
-    }
-
-    fn try_get_period(&self) -> Result<Self::Time, Self::Error> {
-        Ok(self.get_period())
-    }
-
-    fn try_get_duty(
-        &self,
-        channel: Self::Channel,
-    ) -> Result<Self::Duty, Self::Error> {
-        match channel {
-            Self::Channel::Channel1 => self.channels.channel1.try_get_duty(),
-            Self::Channel::Channel2 => self.channels.channel2.try_get_duty(),
-        }
-    }
-
-    fn try_get_max_duty(&self) -> Result<Self::Duty, Self::Error> {
-        Ok(self.get_max_duty())
-    }
-
-    fn try_set_duty(
-        &mut self,
-        channel: Self::Channel,
-        duty: Self::Duty,
-    ) -> Result<(), Self::Error> {
-        match channel {
-            Self::Channel::Channel1 => {
-                self.channels.channel1.try_set_duty(duty)
-            }
-            Self::Channel::Channel2 => {
-                self.channels.channel2.try_set_duty(duty)
+            impl PwmAlpha for CTIMER<Enabled, $state1, $state2, $state3> {
+                type Error = Infallible;
+                type Channel = $channels;
+                type Time = u32;
+                type Duty = u32;
+
+                fn try_disable(
+                    &mut self,
+                    channel: Self::Channel,
+                ) -> Result<(), Self::Error> {
+                    match channel {
+                        $(
+                            $channels::$channel =>
+                                self.channels.$field.try_disable(),
+                        )*
+                    }
+                }
+
+                fn try_enable(
+                    &mut self,
+                    channel: Self::Channel,
+                ) -> Result<(), Self::Error> {
+                    match channel {
+                        $(
+                            $channels::$channel =>
+                                self.channels.$field.try_enable(),
+                        )*
+                    }
+                }
+
+                fn try_get_period(&self) -> Result<Self::Time, Self::Error> {
+                    Ok(self.get_period())
+                }
+
+                fn try_get_duty(
+                    &self,
+                    channel: Self::Channel,
+                ) -> Result<Self::Duty, Self::Error> {
+                    match channel {
+                        $(
+                            $channels::$channel =>
+                                self.channels.$field.try_get_duty(),
+                        )*
+                    }
+                }
+
+                fn try_get_max_duty(
+                    &self,
+                ) -> Result<Self::Duty, Self::Error> {
+                    Ok(self.get_max_duty())
+                }
+
+                fn try_set_duty(
+                    &mut self,
+                    channel: Self::Channel,
+                    duty: Self::Duty,
+                ) -> Result<(), Self::Error> {
+                    match channel {
+                        $(
+                            $channels::$channel =>
+                                self.channels.$field.try_set_duty(duty),
+                        )*
+                    }
+                }
+
+                fn try_set_period<P>(
+                    &mut self,
+                    period: P,
+                ) -> Result<(), Self::Error>
+                where
+                    P: Into<Self::Time>,
+                {
+                    self.set_period(period.into());
+                    Ok(())
+                }
             }
-        }
-    }
-
-    fn try_set_period<P>(&mut self, period: P) -> Result<(), Self::Error>
-    where
-        P: Into<Self::Time>,
-    {
-        Ok(self.set_period(period.into()))
-    }
+        )*
+    };
 }
 
-impl PwmAlpha for CTIMER<Enabled, Attached, Attached, Attached> {
-    type Error = Infallible;
-    type Channel = Channels123;
-    type Time = u32;
-    type Duty = u32;
-
-    fn try_disable(
-        &mut self,
-        channel: Self::Channel,
-    ) -> Result<(), Self::Error> {
-        match channel {
-            Self::Channel::Channel1 => self.channels.channel1.try_disable(),
-            Self::Channel::Channel2 => self.channels.channel2.try_disable(),
-            Self::Channel::Channel3 => self.channels.channel3.try_disable(),
-        }
-    }
-
-    fn try_enable(
-        &mut self,
-        channel: Self::Channel,
-    ) -> Result<(), Self::Error> {
-        match channel {
-            Self::Channel::Channel1 => self.channels.channel1.try_enable(),
-            Self::Channel::Channel2 => self.channels.channel2.try_enable(),
-            Self::Channel::Channel3 => self.channels.channel3.try_enable(),
-        }
-    }
-
-    fn try_get_period(&self) -> Result<Self::Time, Self::Error> {
-        Ok(self.get_period())
-    }
-
-    fn try_get_duty(
-        &self,
-        channel: Self::Channel,
-    ) -> Result<Self::Duty, Self::Error> {
-        match channel {
-            Self::Channel::Channel1 => self.channels.channel1.try_get_duty(),
-            Self::Channel::Channel2 => self.channels.channel2.try_get_duty(),
-            Self::Channel::Channel3 => self.channels.channel3.try_get_duty(),
-        }
-    }
-
-    fn try_get_max_duty(&self) -> Result<Self::Duty, Self::Error> {
-        Ok(self.get_max_duty())
-    }
-
-    fn try_set_duty(
-        &mut self,
-        channel: Self::Channel,
-        duty: Self::Duty,
-    ) -> Result<(), Self::Error> {
-        match channel {
-            Self::Channel::Channel1 => {
-                self.channels.channel1.try_set_duty(duty)
-            }
-            Self::Channel::Channel2 => {
-                self.channels.channel2.try_set_duty(duty)
-            }
-            Self::Channel::Channel3 => {
-                self.channels.channel3.try_set_duty(duty)
-            }
-        }
-    }
-
-    fn try_set_period<P>(&mut self, period: P) -> Result<(), Self::Error>
-    where
-        P: Into<Self::Time>,
-    {
-        Ok(self.set_period(period.into()))
-    }
+pwm! {
+    Channels1, (Attached, Detached, Detached), [Channel1: channel1];
+    Channels2, (Detached, Attached, Detached), [Channel2: channel2];
+    Channels3, (Detached, Detached, Attached), [Channel3: channel3];
+    Channels12, (Attached, Attached, Detached),
+        [Channel1: channel1, Channel2: channel2];
+    Channels13, (Attached, Detached, Attached),
+        [Channel1: channel1, Channel3: channel3];
+    Channels23, (Detached, Attached, Attached),
+        [Channel2: channel2, Channel3: channel3];
+    Channels123, (Attached, Attached, Attached),
+        [Channel1: channel1, Channel2: channel2, Channel3: channel3];
 }
 
 /// The available channels, if only channel 1 is attached
@@ -572,6 +728,20 @@ pub enum Channels1 {
     Channel1,
 }
 
+/// The available channels, if only channel 2 is attached
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Channels2 {
+    /// Channel 2
+    Channel2,
+}
+
+/// The available channels, if only channel 3 is attached
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Channels3 {
+    /// Channel 3
+    Channel3,
+}
+
 /// The available channels, if only channels 1 and 2 are attached
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Channels12 {
@@ -582,7 +752,27 @@ pub enum Channels12 {
     Channel2,
 }
 
-/// The available channels, if channels 1, 2, and 2 are attached
+/// The available channels, if only channels 1 and 3 are attached
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Channels13 {
+    /// Channel 1
+    Channel1,
+
+    /// Channel 3
+    Channel3,
+}
+
+/// The available channels, if only channels 2 and 3 are attached
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Channels23 {
+    /// Channel 2
+    Channel2,
+
+    /// Channel 3
+    Channel3,
+}
+
+/// The available channels, if channels 1, 2, and 3 are attached
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Channels123 {
     /// Channel 1
@@ -595,19 +785,25 @@ pub enum Channels123 {
     Channel3,
 }
 
-impl From<Channels1> for Channels123 {
-    fn from(from: Channels1) -> Self {
-        match from {
-            Channels1::Channel1 => Self::Channel1,
-        }
-    }
+macro_rules! into_channels123 {
+    ($($channels:ident: $($channel:ident),*;)*) => {
+        $(
+            impl From<$channels> for Channels123 {
+                fn from(from: $channels) -> Self {
+                    match from {
+                        $($channels::$channel => Self::$channel,)*
+                    }
+                }
+            }
+        )*
+    };
 }
 
-impl From<Channels12> for Channels123 {
-    fn from(from: Channels12) -> Self {
-        match from {
-            Channels12::Channel1 => Self::Channel1,
-            Channels12::Channel2 => Self::Channel2,
-        }
-    }
+into_channels123! {
+    Channels1: Channel1;
+    Channels2: Channel2;
+    Channels3: Channel3;
+    Channels12: Channel1, Channel2;
+    Channels13: Channel1, Channel3;
+    Channels23: Channel2, Channel3;
 }
@@ -0,0 +1,317 @@
+use embedded_hal::timer::{CountDown, Periodic};
+use embedded_hal_alpha::timer::{
+    CountDown as CountDownAlpha, Periodic as PeriodicAlpha,
+};
+use embedded_time::{clock, fraction::Fraction, Instant};
+use void::Void;
+
+use crate::{
+    delay::{impl_delay, Blocking},
+    init_state::Disabled,
+    pac::{Interrupt, CTIMER0, NVIC},
+    syscon,
+};
+
+use super::{
+    capture::Captures,
+    channel::state::Detached,
+    peripheral::{self, CTIMER},
+};
+
+/// The CTIMER, operating as a general-purpose 32-bit timer
+///
+/// Use [`CTIMER::enable_timer`] to gain access to an instance of this struct.
+///
+/// The timer counts up at a rate of `HZ` ticks per second, and wraps around
+/// once it reaches the maximum value of `u32`. It never stops or resets while
+/// counting down, which means it can be used as a monotonic timebase via its
+/// [`embedded_time::Clock`] implementation, and as a periodic timer via its
+/// [`CountDown`] implementation, at the same time.
+///
+/// Counting down is done using match register 0. Its match interrupt is enabled
+/// while counting down, and can be routed to the CPU using
+/// [`Timer::enable_in_nvic`].
+///
+/// The capture channels in the `captures` field can be used to timestamp
+/// external events. Please refer to the [`capture`] module for details.
+///
+/// The timer can also be used for blocking delays. Those only read the counter
+/// value, so they don't interfere with counting down.
+///
+/// # `embedded-hal` traits
+/// - [`embedded_hal::timer::CountDown`]
+/// - [`embedded_hal::timer::Periodic`]
+/// - [`embedded_hal::blocking::delay::DelayUs`]
+/// - [`embedded_hal::blocking::delay::DelayMs`]
+///
+/// # `embedded-time` traits
+/// - [`embedded_time::Clock`]
+///
+/// # RTIC
+///
+/// If the `rtic-monotonic` feature is enabled, `Timer` can be used as an RTIC
+/// monotonic timer. It uses match register 1 for the compare interrupt. Don't
+/// count down while it is in use, as the match interrupt of the count down
+/// would keep triggering the CTIMER interrupt.
+///
+/// Unlike the MRT-based `mrt::Monotonic`, the timer is not extended to 64 bits
+/// in software. Its instants are 32-bit tick counts that wrap around after
+/// 2^32 ticks, and RTIC can only schedule tasks less than half of that into
+/// the future. At 1 MHz, the counter wraps about every 71.6 minutes, which
+/// limits the scheduling horizon to about 35.8 minutes. Choose `HZ`
+/// accordingly, or use the MRT-based monotonic for longer delays.
+///
+/// [`embedded_hal::timer::CountDown`]: #impl-CountDown
+/// [`embedded_hal::timer::Periodic`]: #impl-Periodic
+/// [`embedded_hal::blocking::delay::DelayUs`]: #impl-DelayUs%3Cu32%3E
+/// [`embedded_hal::blocking::delay::DelayMs`]: #impl-DelayMs%3Cu32%3E
+/// [`embedded_time::Clock`]: #impl-Clock
+/// [`capture`]: super::capture
+pub struct Timer<const HZ: u32> {
+    /// The capture channels of this CTIMER
+    pub captures: Captures,
+
+    inner: CTIMER0,
+    clock: u32,
+    period: u32,
+}
+
+impl<const HZ: u32> Timer<HZ> {
+    /// Enables the CTIMER and configures it as a general-purpose timer
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `HZ` is zero, larger than the CTIMER clock frequency, or
+    /// doesn't divide it evenly.
+    pub(super) fn new(
+        inner: CTIMER0,
+        clock: u32,
+        syscon: &mut syscon::Handle,
+    ) -> Self {
+        assert!(HZ != 0 && HZ <= clock);
+        assert!(clock % HZ == 0);
+
+        peripheral::enable(&inner, syscon);
+
+        let prescaler = clock / HZ - 1;
+        inner.pr.write(|w| unsafe { w.prval().bits(prescaler) });
+
+        // Reset the counter and start it.
+        inner.tcr.write(|w| w.crst().enabled());
+        inner.tcr.write(|w| w.cen().set_bit());
+
+        Self {
+            captures: Captures::new(),
+            inner,
+            clock,
+            period: 0,
+        }
+    }
+
+    /// Start counting down
+    ///
+    /// Counts down `ticks` ticks from the current counter value, without
+    /// resetting the counter. Once the count down has finished, it is
+    /// restarted automatically with the same number of ticks, without
+    /// accumulating any drift.
+    ///
+    /// If `ticks` is zero, a period of one tick is used instead.
+    pub fn start(&mut self, ticks: u32) {
+        self.period = ticks.max(1);
+
+        let target = self.value().wrapping_add(self.period);
+        self.inner.mr[0].write(|w| unsafe { w.match_().bits(target) });
+
+        self.inner.ir.write(|w| w.mr0int().set_bit());
+        self.inner.mcr.modify(|_, w| w.mr0i().set_bit());
+    }
+
+    /// Stop counting down
+    ///
+    /// Disables the match interrupt. The counter itself keeps running.
+    pub fn cancel(&mut self) {
+        self.inner.mcr.modify(|_, w| w.mr0i().clear_bit());
+        self.inner.ir.write(|w| w.mr0int().set_bit());
+    }
+
+    /// Returns the current counter value
+    pub fn value(&self) -> u32 {
+        self.inner.tc.read().tcval().bits()
+    }
+
+    /// Enable the CTIMER interrupt in the NVIC
+    ///
+    /// This only enables the interrupt in the NVIC. The match interrupt itself
+    /// is enabled while counting down.
+    pub fn enable_in_nvic(&mut self) {
+        // Safe, because there's no critical section here that this could
+        // interfere with.
+        unsafe { NVIC::unmask(Interrupt::CTIMER0) };
+    }
+
+    /// Disable the CTIMER interrupt in the NVIC
+    ///
+    /// This only disables the interrupt in the NVIC. It doesn't change anything
+    /// about the interrupt configuration within the CTIMER.
+    pub fn disable_in_nvic(&mut self) {
+        NVIC::mask(Interrupt::CTIMER0);
+    }
+
+    /// Clear the CTIMER's interrupt pending flag in the NVIC
+    ///
+    /// This only clears the interrupt's pending flag in the NVIC. It does not
+    /// affect any of the interrupt-related flags in the peripheral.
+    pub fn clear_nvic_pending(&mut self) {
+        NVIC::unpend(Interrupt::CTIMER0);
+    }
+
+    /// Disable the CTIMER
+    ///
+    /// Consumes this instance of `Timer` and returns a disabled `CTIMER`, which
+    /// can be enabled again in any mode. Capture channels that have been
+    /// attached need to be detached and moved back into the `captures` field
+    /// first.
+    pub fn disable(
+        self,
+        syscon: &mut syscon::Handle,
+    ) -> CTIMER<Disabled, Detached, Detached, Detached> {
+        self.inner.tcr.write(|w| w.cen().clear_bit());
+        self.inner.mcr.reset();
+        self.inner.ccr.reset();
+        // Sound, as writing ones to all flags just clears them.
+        self.inner.ir.write(|w| unsafe { w.bits(0xff) });
+
+        syscon.disable_clock(&self.inner);
+
+        CTIMER::disabled(self.inner, self.clock)
+    }
+
+    /// Return the raw peripheral
+    ///
+    /// This method serves as an escape hatch from the HAL API. It returns the
+    /// raw peripheral, allowing you to do whatever you want with it, without
+    /// limitations imposed by the API.
+    ///
+    /// If you are using this method because a feature you need is missing from
+    /// the HAL API, please [open an issue] or, if an issue for your feature
+    /// request already exists, comment on the existing issue, so we can
+    /// prioritize it accordingly.
+    ///
+    /// [open an issue]: https://github.com/lpc-rs/lpc8xx-hal/issues
+    pub fn free(self) -> CTIMER0 {
+        self.inner
+    }
+
+    /// Non-blockingly "waits" until the count down finishes
+    fn wait(&mut self) -> nb::Result<(), Void> {
+        if self.inner.ir.read().mr0int().bit_is_clear() {
+            return Err(nb::Error::WouldBlock);
+        }
+        self.inner.ir.write(|w| w.mr0int().set_bit());
+
+        // Schedule the next match relative to the last one, to prevent drift.
+        // If we're so late that this is already in the past, start over from
+        // the current counter value.
+        let last = self.inner.mr[0].read().match_().bits();
+        let mut next = last.wrapping_add(self.period);
+        let now = self.value();
+        if now.wrapping_sub(last) >= self.period {
+            next = now.wrapping_add(self.period);
+        }
+        self.inner.mr[0].write(|w| unsafe { w.match_().bits(next) });
+
+        Ok(())
+    }
+}
+
+impl<const HZ: u32> CountDown for Timer<HZ> {
+    /// The timer operates in ticks of `HZ` ticks per second
+    type Time = u32;
+
+    fn start<Time>(&mut self, count: Time)
+    where
+        Time: Into<Self::Time>,
+    {
+        self.start(count.into());
+    }
+
+    fn wait(&mut self) -> nb::Result<(), Void> {
+        self.wait()
+    }
+}
+
+impl<const HZ: u32> CountDownAlpha for Timer<HZ> {
+    type Error = Void;
+
+    /// The timer operates in ticks of `HZ` ticks per second
+    type Time = u32;
+
+    fn try_start<Time>(&mut self, count: Time) -> Result<(), Self::Error>
+    where
+        Time: Into<Self::Time>,
+    {
+        self.start(count.into());
+        Ok(())
+    }
+
+    fn try_wait(&mut self) -> nb::Result<(), Self::Error> {
+        self.wait()
+    }
+}
+
+impl<const HZ: u32> Periodic for Timer<HZ> {}
+
+impl<const HZ: u32> PeriodicAlpha for Timer<HZ> {}
+
+impl<const HZ: u32> Blocking for Timer<HZ> {
+    fn ticks_per_second(&self) -> u32 {
+        HZ
+    }
+
+    fn block(&mut self, mut ticks: u64) {
+        // Wait in chunks of less than half the counter range, so a chunk is
+        // reliably detected as finished, even if we're interrupted for a while.
+        const MAX_CHUNK: u64 = 0x7fff_ffff;
+
+        while ticks != 0 {
+            let chunk = ticks.min(MAX_CHUNK) as u32;
+            let start = self.value();
+            while self.value().wrapping_sub(start) < chunk {}
+            ticks -= chunk as u64;
+        }
+    }
+}
+
+impl_delay!([const HZ: u32] Timer<HZ>, []);
+
+impl<const HZ: u32> embedded_time::Clock for Timer<HZ> {
+    type T = u32;
+
+    const SCALING_FACTOR: Fraction = Fraction::new(1, HZ);
+
+    fn try_now(&self) -> Result<Instant<Self>, clock::Error> {
+        Ok(Instant::new(self.value()))
+    }
+}
+
+#[cfg(feature = "rtic-monotonic")]
+impl<const HZ: u32> rtic_monotonic::Monotonic for Timer<HZ> {
+    unsafe fn reset(&mut self) {
+        self.inner.tcr.modify(|_, w| w.crst().enabled());
+        self.inner.tcr.modify(|_, w| w.crst().disabled());
+    }
+
+    fn set_compare(&mut self, instant: &Instant<Self>) {
+        let ticks = instant.duration_since_epoch().integer();
+        self.inner.mr[1].write(|w| unsafe { w.match_().bits(ticks) });
+        self.inner.mcr.modify(|_, w| w.mr1i().set_bit());
+    }
+
+    fn clear_compare_flag(&mut self) {
+        self.inner.ir.write(|w| w.mr1int().set_bit());
+    }
+
+    fn disable_timer(&mut self) {
+        self.inner.mcr.modify(|_, w| w.mr1i().clear_bit());
+    }
+}
@@ -0,0 +1,432 @@
+//! Contains types related to CTIMER match outputs
+//!
+//! In waveform mode, each of the four match registers can change the level of
+//! its `T0_MATn` output, once the counter reaches the match value. The output
+//! can be set, cleared, or toggled, and the match can optionally stop or reset
+//! the counter. This allows generating precise pulses and square waves without
+//! any CPU involvement.
+//!
+//! Use [`CTIMER::enable_waveform`] to enter this mode.
+//!
+//! # Example
+//!
+//! Generate a single pulse with a width of 100 µs:
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     ctimer::waveform::{Action, OnMatch},
+//!     Peripherals,
+//! };
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let swm = p.SWM.split();
+//! let mut syscon = p.SYSCON.split();
+//!
+//! let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+//!
+//! let (mat0, _) = swm.movable_functions.t0_mat0.assign(
+//!     p.pins.pio1_2.into_swm_pin(),
+//!     &mut swm_handle,
+//! );
+//!
+//! // Count in microseconds
+//! let (mut timer, outputs) =
+//!     p.CTIMER0.enable_waveform::<1_000_000>(&mut syscon.handle);
+//! let mut output = outputs.output0.attach(mat0);
+//!
+//! output.set_level(true);
+//! output.set_match(100, Action::Clear, OnMatch::Stop);
+//! timer.start();
+//! ```
+//!
+//! [`CTIMER::enable_waveform`]: super::CTIMER::enable_waveform
+
+use core::marker::PhantomData;
+
+use cortex_m::interrupt;
+
+use crate::{
+    init_state::Disabled,
+    pac::{Interrupt, CTIMER0, NVIC},
+    swm, syscon,
+};
+
+use super::{
+    channel::state::{Attached, Detached},
+    peripheral::{self, CTIMER},
+};
+
+/// The CTIMER, operating in waveform mode
+///
+/// Use [`CTIMER::enable_waveform`] to gain access to an instance of this
+/// struct. Please refer to the [module documentation] for details.
+///
+/// The counter counts up at a rate of `HZ` ticks per second. It is stopped
+/// initially, so the match outputs can be configured before calling
+/// [`WaveformTimer::start`].
+///
+/// [`CTIMER::enable_waveform`]: super::CTIMER::enable_waveform
+/// [module documentation]: index.html
+pub struct WaveformTimer<const HZ: u32> {
+    inner: CTIMER0,
+    clock: u32,
+}
+
+impl<const HZ: u32> WaveformTimer<HZ> {
+    /// Enables the CTIMER and configures it for waveform mode
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `HZ` is zero, larger than the CTIMER clock frequency, or
+    /// doesn't divide it evenly.
+    pub(super) fn new(
+        inner: CTIMER0,
+        clock: u32,
+        syscon: &mut syscon::Handle,
+    ) -> (Self, MatchOutputs) {
+        assert!(HZ != 0 && HZ <= clock);
+        assert!(clock % HZ == 0);
+
+        peripheral::enable(&inner, syscon);
+
+        let prescaler = clock / HZ - 1;
+        inner.pr.write(|w| unsafe { w.prval().bits(prescaler) });
+
+        // Reset the counter, but leave it stopped.
+        inner.tcr.write(|w| w.crst().enabled());
+        inner.tcr.write(|w| w.crst().disabled());
+
+        (Self { inner, clock }, MatchOutputs::new())
+    }
+
+    /// Start the counter
+    ///
+    /// The counter continues from its current value. This also restarts the
+    /// counter after it has been stopped by a match.
+    pub fn start(&mut self) {
+        self.inner.tcr.write(|w| w.cen().set_bit());
+    }
+
+    /// Stop the counter
+    pub fn stop(&mut self) {
+        self.inner.tcr.write(|w| w.cen().clear_bit());
+    }
+
+    /// Indicates whether the counter is running
+    pub fn is_running(&self) -> bool {
+        self.inner.tcr.read().cen().bit_is_set()
+    }
+
+    /// Reset the counter to zero
+    pub fn reset(&mut self) {
+        let running = self.is_running();
+        self.inner.tcr.write(|w| w.crst().enabled());
+        self.inner.tcr.write(|w| w.cen().bit(running));
+    }
+
+    /// Returns the current counter value
+    pub fn value(&self) -> u32 {
+        self.inner.tc.read().tcval().bits()
+    }
+
+    /// Enable the CTIMER interrupt in the NVIC
+    ///
+    /// This only enables the interrupt in the NVIC. The match interrupts
+    /// themselves are enabled by [`MatchOutput::set_match`].
+    pub fn enable_in_nvic(&mut self) {
+        // Safe, because there's no critical section here that this could
+        // interfere with.
+        unsafe { NVIC::unmask(Interrupt::CTIMER0) };
+    }
+
+    /// Disable the CTIMER interrupt in the NVIC
+    ///
+    /// This only disables the interrupt in the NVIC. It doesn't change anything
+    /// about the interrupt configuration within the CTIMER.
+    pub fn disable_in_nvic(&mut self) {
+        NVIC::mask(Interrupt::CTIMER0);
+    }
+
+    /// Clear the CTIMER's interrupt pending flag in the NVIC
+    ///
+    /// This only clears the interrupt's pending flag in the NVIC. It does not
+    /// affect any of the interrupt-related flags in the peripheral.
+    pub fn clear_nvic_pending(&mut self) {
+        NVIC::unpend(Interrupt::CTIMER0);
+    }
+
+    /// Disable the CTIMER
+    ///
+    /// Consumes this instance of `WaveformTimer`, as well as the detached
+    /// match outputs, and returns a disabled `CTIMER`, which can be enabled
+    /// again in any mode.
+    pub fn disable(
+        self,
+        _: MatchOutputs,
+        syscon: &mut syscon::Handle,
+    ) -> CTIMER<Disabled, Detached, Detached, Detached> {
+        self.inner.tcr.write(|w| w.cen().clear_bit());
+        self.inner.mcr.reset();
+        self.inner.emr.reset();
+        // Sound, as writing ones to all flags just clears them.
+        self.inner.ir.write(|w| unsafe { w.bits(0xff) });
+
+        syscon.disable_clock(&self.inner);
+
+        CTIMER::disabled(self.inner, self.clock)
+    }
+
+    /// Return the raw peripheral
+    ///
+    /// This method serves as an escape hatch from the HAL API. It returns the
+    /// raw peripheral, allowing you to do whatever you want with it, without
+    /// limitations imposed by the API.
+    ///
+    /// If you are using this method because a feature you need is missing from
+    /// the HAL API, please [open an issue] or, if an issue for your feature
+    /// request already exists, comment on the existing issue, so we can
+    /// prioritize it accordingly.
+    ///
+    /// [open an issue]: https://github.com/lpc-rs/lpc8xx-hal/issues
+    pub fn free(self) -> CTIMER0 {
+        self.inner
+    }
+}
+
+/// A CTIMER match output
+///
+/// Match outputs start out detached. Use [`MatchOutput::attach`] to assign
+/// the output function.
+pub struct MatchOutput<M, State> {
+    channel: PhantomData<M>,
+    _state: PhantomData<State>,
+}
+
+impl<M, State> MatchOutput<M, State> {
+    fn new() -> Self {
+        Self {
+            channel: PhantomData,
+            _state: PhantomData,
+        }
+    }
+}
+
+impl<M> MatchOutput<M, Detached>
+where
+    M: Trait,
+{
+    /// Attach the output function
+    pub fn attach<Pin>(
+        self,
+        _: swm::Function<M::Output, swm::state::Assigned<Pin>>,
+    ) -> MatchOutput<M, Attached> {
+        MatchOutput::new()
+    }
+}
+
+impl<M> MatchOutput<M, Attached>
+where
+    M: Trait,
+{
+    /// Configure what happens when the counter reaches `value`
+    ///
+    /// `action` determines how the output changes, `on_match` what happens to
+    /// the counter. The match interrupt is enabled too, which makes the match
+    /// detectable using [`MatchOutput::match_occurred`].
+    pub fn set_match(&mut self, value: u32, action: Action, on_match: OnMatch) {
+        let ctimer = ctimer();
+        let id = M::ID;
+
+        ctimer.mr[id as usize].write(|w| unsafe { w.match_().bits(value) });
+        self.clear_flag();
+
+        let mcr = match on_match {
+            OnMatch::Continue => MCR_INTERRUPT,
+            OnMatch::Reset => MCR_INTERRUPT | MCR_RESET,
+            OnMatch::Stop => MCR_INTERRUPT | MCR_STOP,
+        };
+
+        interrupt::free(|_| {
+            // Sound, as we only change the bits of this match channel, and all
+            // bit patterns are valid.
+            ctimer.mcr.modify(|r, w| unsafe {
+                w.bits(r.bits() & !(0x7 << (3 * id)) | mcr << (3 * id))
+            });
+            ctimer.emr.modify(|r, w| unsafe {
+                w.bits(
+                    r.bits() & !(0x3 << (4 + 2 * id))
+                        | (action as u32) << (4 + 2 * id),
+                )
+            });
+        });
+    }
+
+    /// Disable the match
+    ///
+    /// The output keeps its current level.
+    pub fn clear_match(&mut self) {
+        let ctimer = ctimer();
+        let id = M::ID;
+
+        interrupt::free(|_| {
+            // Sound, as we only clear the bits of this match channel.
+            ctimer.mcr.modify(|r, w| unsafe {
+                w.bits(r.bits() & !(0x7 << (3 * id)))
+            });
+            ctimer.emr.modify(|r, w| unsafe {
+                w.bits(r.bits() & !(0x3 << (4 + 2 * id)))
+            });
+        });
+        self.clear_flag();
+    }
+
+    /// Disable the match and detach the output
+    ///
+    /// The output function stays assigned to its pin, which keeps its current
+    /// level.
+    pub fn detach(mut self) -> MatchOutput<M, Detached> {
+        self.clear_match();
+        MatchOutput::new()
+    }
+
+    /// Set the output level
+    ///
+    /// `true` means the output is high, `false` that it is low. This can be
+    /// used to set the initial level, before the next match changes it.
+    pub fn set_level(&mut self, high: bool) {
+        let id = M::ID;
+
+        interrupt::free(|_| {
+            // Sound, as we only change the bit of this match channel.
+            ctimer().emr.modify(|r, w| unsafe {
+                w.bits(r.bits() & !(0x1 << id) | (high as u32) << id)
+            });
+        });
+    }
+
+    /// Returns the current output level
+    ///
+    /// `true` means the output is high, `false` that it is low.
+    pub fn level(&self) -> bool {
+        ctimer().emr.read().bits() & 0x1 << M::ID != 0
+    }
+
+    /// Indicates whether a match has occurred
+    ///
+    /// Clears the match interrupt flag, if it was set.
+    pub fn match_occurred(&mut self) -> bool {
+        if ctimer().ir.read().bits() & 0x1 << M::ID == 0 {
+            return false;
+        }
+        self.clear_flag();
+
+        true
+    }
+
+    fn clear_flag(&mut self) {
+        // Sound, as writing zeros to the register has no effect.
+        ctimer().ir.write(|w| unsafe { w.bits(0x1 << M::ID) });
+    }
+}
+
+/// The action performed on a match output, when a match occurs
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Action {
+    /// Leave the output unchanged
+    DoNothing = 0b00,
+
+    /// Set the output low
+    Clear = 0b01,
+
+    /// Set the output high
+    Set = 0b10,
+
+    /// Toggle the output
+    Toggle = 0b11,
+}
+
+/// What happens to the counter, when a match occurs
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OnMatch {
+    /// The counter continues counting
+    Continue,
+
+    /// The counter is reset to zero and continues counting
+    Reset,
+
+    /// The counter is stopped
+    Stop,
+}
+
+const MCR_INTERRUPT: u32 = 0b001;
+const MCR_RESET: u32 = 0b010;
+const MCR_STOP: u32 = 0b100;
+
+/// Contains all CTIMER match outputs
+///
+/// Returned by [`CTIMER::enable_waveform`]. Needs to be passed back to
+/// [`WaveformTimer::disable`], with all outputs detached.
+///
+/// [`CTIMER::enable_waveform`]: super::CTIMER::enable_waveform
+#[allow(missing_docs)]
+pub struct MatchOutputs {
+    pub output0: MatchOutput<Match0, Detached>,
+    pub output1: MatchOutput<Match1, Detached>,
+    pub output2: MatchOutput<Match2, Detached>,
+    pub output3: MatchOutput<Match3, Detached>,
+}
+
+impl MatchOutputs {
+    fn new() -> Self {
+        Self {
+            output0: MatchOutput::new(),
+            output1: MatchOutput::new(),
+            output2: MatchOutput::new(),
+            output3: MatchOutput::new(),
+        }
+    }
+}
+
+/// Implemented for all CTIMER match outputs
+pub trait Trait: private::Sealed {
+    /// Identifies the match channel
+    const ID: u8;
+
+    /// The SWM function that needs to be assigned to this output's pin
+    type Output;
+}
+
+macro_rules! outputs {
+    ($($channel:ident, $id:expr, $output:ident;)*) => {
+        $(
+            /// Identifies a CTIMER match output
+            pub struct $channel;
+
+            impl private::Sealed for $channel {}
+
+            impl Trait for $channel {
+                const ID: u8 = $id;
+                type Output = swm::$output;
+            }
+        )*
+    };
+}
+
+outputs! {
+    Match0, 0, T0_MAT0;
+    Match1, 1, T0_MAT1;
+    Match2, 2, T0_MAT2;
+    Match3, 3, T0_MAT3;
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+fn ctimer() -> &'static crate::pac::ctimer0::RegisterBlock {
+    // Sound, as match outputs only access their own match register and their
+    // own bits of IR, MCR, and EMR. Modifications of MCR and EMR are protected
+    // by a critical section.
+    unsafe { &*CTIMER0::ptr() }
+}
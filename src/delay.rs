@@ -3,6 +3,10 @@
 //! Please be aware of potential overflows when using `delay_us`.
 //! E.g. at 30MHz the maximum delay is 146 seconds.
 //!
+//! If SysTick is needed for something else, like an RTOS kernel, the delay
+//! traits are also implemented by [`mrt::Channel`], by `ctimer::Timer` (on
+//! LPC845), and by [`WKT`], which is suited for long, coarse delays.
+//!
 //! # Example
 //!
 //! ``` no_run
@@ -19,6 +23,9 @@
 //!     delay.delay_ms(1_000_u16);
 //! }
 //! ```
+//!
+//! [`mrt::Channel`]: crate::mrt::Channel
+//! [`WKT`]: crate::wkt::WKT
 
 use cortex_m::peripheral::syst::SystClkSource;
 
@@ -189,3 +196,91 @@ impl DelayUsAlpha<u8> for Delay {
         Ok(self.delay_us(us))
     }
 }
+
+/// A timer that can block for a number of ticks
+///
+/// Timers that implement this trait get their implementations of the delay
+/// traits from `impl_delay!`.
+pub(crate) trait Blocking {
+    /// Returns the frequency of the timer, in ticks per second
+    fn ticks_per_second(&self) -> u32;
+
+    /// Block for the given number of ticks
+    fn block(&mut self, ticks: u64);
+}
+
+/// Converts a duration into ticks of a timer, rounding up
+pub(crate) fn ticks(duration: u32, units_per_second: u64, hz: u32) -> u64 {
+    (duration as u64 * hz as u64 + units_per_second - 1) / units_per_second
+}
+
+/// Implements the delay traits for a timer that implements [`Blocking`]
+///
+/// Takes the generic parameters of the implementations, the timer type, and
+/// the bounds of the where clause.
+macro_rules! impl_delay {
+    ([$($generics:tt)*] $timer:ty, [$($bounds:tt)*]) => {
+        impl_delay!(@impl u32, [$($generics)*] $timer, [$($bounds)*]);
+        impl_delay!(@impl u16, [$($generics)*] $timer, [$($bounds)*]);
+        impl_delay!(@impl u8, [$($generics)*] $timer, [$($bounds)*]);
+    };
+    (@impl $int:ty, [$($generics:tt)*] $timer:ty, [$($bounds:tt)*]) => {
+        impl<$($generics)*> ::embedded_hal::blocking::delay::DelayUs<$int>
+            for $timer
+        where
+            $($bounds)*
+        {
+            /// Pauses execution for `us` microseconds
+            fn delay_us(&mut self, us: $int) {
+                use $crate::delay::{ticks, Blocking};
+
+                let hz = self.ticks_per_second();
+                self.block(ticks(us.into(), 1_000_000, hz));
+            }
+        }
+
+        impl<$($generics)*> ::embedded_hal_alpha::blocking::delay::DelayUs<$int>
+            for $timer
+        where
+            $($bounds)*
+        {
+            type Error = ::void::Void;
+
+            /// Pauses execution for `us` microseconds
+            fn try_delay_us(&mut self, us: $int) -> Result<(), Self::Error> {
+                ::embedded_hal::blocking::delay::DelayUs::delay_us(self, us);
+                Ok(())
+            }
+        }
+
+        impl<$($generics)*> ::embedded_hal::blocking::delay::DelayMs<$int>
+            for $timer
+        where
+            $($bounds)*
+        {
+            /// Pauses execution for `ms` milliseconds
+            fn delay_ms(&mut self, ms: $int) {
+                use $crate::delay::{ticks, Blocking};
+
+                let hz = self.ticks_per_second();
+                self.block(ticks(ms.into(), 1_000, hz));
+            }
+        }
+
+        impl<$($generics)*> ::embedded_hal_alpha::blocking::delay::DelayMs<$int>
+            for $timer
+        where
+            $($bounds)*
+        {
+            type Error = ::void::Void;
+
+            /// Pauses execution for `ms` milliseconds
+            fn try_delay_ms(&mut self, ms: $int) -> Result<(), Self::Error> {
+                ::embedded_hal::blocking::delay::DelayMs::delay_ms(self, ms);
+                Ok(())
+            }
+        }
+    };
+}
+
+pub(crate) use impl_delay;
@@ -0,0 +1,521 @@
+//! Tickless idle
+//!
+//! Puts the microcontroller into the deepest sleep mode that is appropriate
+//! for the time until the next deadline. The deadline is provided by a
+//! [`Timebase`], like a [`TimerQueue`] based on the MRT.
+//!
+//! The decision which sleep mode to enter is made by [`Policy`], which doesn't
+//! access any hardware and can be tested on the host. [`Idle`] combines it
+//! with the PMU and the WKT, to actually enter the sleep mode.
+//!
+//! Regular sleep mode doesn't stop any clocks, so the timebase keeps running
+//! and wakes the microcontroller up by itself. In deep-sleep and power-down
+//! modes, the MRT and most other peripherals are stopped. The WKT, running on
+//! the low-power clock, is used to wake the microcontroller up, and to measure
+//! how long it slept. That time is then added to the timebase.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     idle::{Idle, Policy},
+//!     pac::CorePeripherals,
+//!     timer_queue::{MrtTickSource, TimerQueue},
+//!     Peripherals,
+//! };
+//!
+//! let mut cp = CorePeripherals::take().unwrap();
+//! let p = Peripherals::take().unwrap();
+//!
+//! let mut pmu = p.PMU.split();
+//! let mut syscon = p.SYSCON.split();
+//! let mrt = p.MRT0.split(&mut syscon.handle);
+//!
+//! let clock = pmu.low_power_clock.enable(&mut pmu.handle);
+//! let mut wkt = p.WKT.enable(&mut syscon.handle).select_clock(&clock);
+//!
+//! let source = MrtTickSource::new(mrt.mrt0, mrt.mrt1);
+//! let mut queue = TimerQueue::<_, 4>::new(source);
+//!
+//! let mut idle = Idle::new(
+//!     Policy::new(12_000_000),
+//!     &mut pmu.handle,
+//!     &mut cp.SCB,
+//!     &mut syscon.handle,
+//!     &mut wkt,
+//! );
+//!
+//! loop {
+//!     // Handle events here, then sleep until the next one.
+//!     idle.idle(&mut queue);
+//! }
+//! ```
+//!
+//! [`TimerQueue`]: ../timer_queue/struct.TimerQueue.html
+
+use cortex_m::interrupt;
+use embedded_hal::timer::CountDown as _;
+
+use crate::{
+    init_state, mrt,
+    pac::{self, Interrupt, NVIC},
+    pmu::{self, LowPowerClock},
+    syscon::{self, WktWakeup},
+    timer_queue::{MrtTickSource, TickSource, TimerQueue},
+    wkt::WKT,
+};
+
+/// A timebase that can be used for tickless idle
+///
+/// Implemented for [`TimerQueue`], if it's using the MRT as its tick source.
+/// Can also be implemented by mock timebases, to test code that uses it on the
+/// host.
+///
+/// # Example
+///
+/// A mock timebase, based on a [`TimerQueue`] with a fake tick source, can be
+/// used to test how a [`Policy`] and the compensation for the time slept play
+/// together, on the host:
+///
+/// ```
+/// use lpc8xx_hal::{
+///     idle::{Decision, Policy, Timebase},
+///     timer_queue::{Mode, TickSource, TimerQueue},
+/// };
+///
+/// struct FakeTicks {
+///     now: u64,
+///     alarm: u64,
+/// }
+///
+/// impl TickSource for FakeTicks {
+///     fn now(&self) -> u64 {
+///         self.now
+///     }
+///
+///     fn set_alarm(&mut self, delay: u64) {
+///         self.alarm = self.now.saturating_add(delay);
+///     }
+/// }
+///
+/// struct MockTimebase(TimerQueue<FakeTicks, 2>);
+///
+/// impl Timebase for MockTimebase {
+///     fn now(&self) -> u64 {
+///         self.0.source().now
+///     }
+///
+///     fn next_deadline(&self) -> Option<u64> {
+///         self.0.next_deadline()
+///     }
+///
+///     fn compensate(&mut self, ticks: u64) {
+///         self.0.source_mut().now += ticks;
+///         self.0.reschedule();
+///     }
+/// }
+///
+/// // A 1 MHz timebase, and a low-power clock that was measured at 8 kHz
+/// let policy = Policy::new(1_000_000).wkt_frequency(8_000);
+///
+/// let source = FakeTicks { now: 0, alarm: 0 };
+/// let mut timebase = MockTimebase(TimerQueue::new(source));
+/// let timer = timebase.0.start(Mode::OneShot, 100_000).unwrap();
+///
+/// // Sleep until just before the deadline, leaving time to wake up.
+/// let decision = policy.decide(timebase.now(), timebase.next_deadline());
+/// assert_eq!(decision, Decision::PowerDown { wkt_ticks: 796 });
+/// timebase.compensate(policy.wkt_to_ticks(796));
+/// assert_eq!(timebase.now(), 99_500);
+/// assert_eq!(timebase.0.source().alarm, 100_000);
+///
+/// // Woken up early by another interrupt, after 2 of 3 WKT ticks
+/// let decision = policy.decide(timebase.now(), timebase.next_deadline());
+/// assert_eq!(decision, Decision::DeepSleep { wkt_ticks: 3 });
+/// timebase.compensate(policy.wkt_to_ticks(2));
+/// assert_eq!(timebase.now(), 99_750);
+///
+/// // Go back to sleep for the remaining time
+/// let decision = policy.decide(timebase.now(), timebase.next_deadline());
+/// assert_eq!(decision, Decision::DeepSleep { wkt_ticks: 1 });
+/// timebase.compensate(policy.wkt_to_ticks(1));
+/// assert_eq!(timebase.now(), 99_875);
+///
+/// // Too close to the deadline for anything but regular sleep
+/// let decision = policy.decide(timebase.now(), timebase.next_deadline());
+/// assert_eq!(decision, Decision::Sleep);
+///
+/// // Time passed beyond the deadline while sleeping. Rescheduling makes the
+/// // timer fire right away.
+/// timebase.compensate(1_000);
+/// assert_eq!(timebase.0.source().alarm, 100_876);
+/// let mut fired = Vec::new();
+/// timebase.0.source_mut().now = 100_876;
+/// timebase.0.on_interrupt(|id| fired.push(id));
+/// assert_eq!(fired, [timer]);
+/// ```
+///
+/// [`TimerQueue`]: ../timer_queue/struct.TimerQueue.html
+pub trait Timebase {
+    /// Returns the current time in ticks
+    fn now(&self) -> u64;
+
+    /// Returns the next deadline in ticks, if there is one
+    fn next_deadline(&self) -> Option<u64>;
+
+    /// Account for the given number of ticks, during which the timebase was
+    /// stopped
+    fn compensate(&mut self, ticks: u64);
+}
+
+impl<T, A, const N: usize> Timebase for TimerQueue<MrtTickSource<T, A>, N>
+where
+    T: mrt::Trait,
+    A: mrt::Trait,
+{
+    fn now(&self) -> u64 {
+        self.source().now()
+    }
+
+    fn next_deadline(&self) -> Option<u64> {
+        TimerQueue::next_deadline(self)
+    }
+
+    fn compensate(&mut self, ticks: u64) {
+        self.source_mut().compensate(ticks);
+        self.reschedule();
+    }
+}
+
+/// A sleep mode that can be used for tickless idle
+///
+/// Ordered from the lightest to the deepest sleep mode.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Mode {
+    /// Regular sleep mode
+    Sleep,
+
+    /// Deep-sleep mode
+    DeepSleep,
+
+    /// Power-down mode
+    PowerDown,
+}
+
+/// The decision made by [`Policy::decide`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Decision {
+    /// Don't sleep, as the next deadline has already been reached
+    Stay,
+
+    /// Enter regular sleep mode, and let the timebase wake up the
+    /// microcontroller
+    Sleep,
+
+    /// Enter deep-sleep mode, with the WKT set to the given number of ticks
+    DeepSleep {
+        /// The number of ticks of the low-power clock
+        wkt_ticks: u32,
+    },
+
+    /// Enter power-down mode, with the WKT set to the given number of ticks
+    PowerDown {
+        /// The number of ticks of the low-power clock
+        wkt_ticks: u32,
+    },
+}
+
+/// Decides which sleep mode to enter
+///
+/// A deeper sleep mode is only chosen, if the time until the next deadline is
+/// longer than its wake-up latency. The WKT is set up to wake the
+/// microcontroller up early enough, to account for that latency.
+///
+/// The deepest allowed sleep mode can be restricted using
+/// [`Policy::set_deepest`], for example while a peripheral is active that
+/// would be stopped by deeper sleep modes.
+///
+/// # Example
+///
+/// ```
+/// use lpc8xx_hal::idle::{Decision, Mode, Policy};
+///
+/// // A timebase that runs at 1 MHz
+/// let mut policy = Policy::new(1_000_000)
+///     .deep_sleep_latency(100)
+///     .power_down_latency(1_000);
+///
+/// assert_eq!(policy.decide(10, Some(10)), Decision::Stay);
+/// assert_eq!(policy.decide(0, Some(50)), Decision::Sleep);
+/// assert_eq!(
+///     policy.decide(0, Some(500)),
+///     Decision::DeepSleep { wkt_ticks: 4 },
+/// );
+/// assert_eq!(
+///     policy.decide(0, Some(101_000)),
+///     Decision::PowerDown { wkt_ticks: 1_000 },
+/// );
+///
+/// // Wake-up from power-down is slow, so deep-sleep is used for shorter
+/// // intervals.
+/// assert_eq!(
+///     policy.decide(0, Some(1_000)),
+///     Decision::DeepSleep { wkt_ticks: 9 },
+/// );
+///
+/// // Without a deadline, the WKT is only used to measure the time slept.
+/// assert_eq!(
+///     policy.decide(0, None),
+///     Decision::PowerDown { wkt_ticks: u32::MAX },
+/// );
+///
+/// // A peripheral that doesn't work in deep-sleep mode is active.
+/// policy.set_deepest(Mode::Sleep);
+/// assert_eq!(policy.decide(0, Some(101_000)), Decision::Sleep);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Policy {
+    timebase_hz: u32,
+    wkt_hz: u32,
+    deepest: Mode,
+    deep_sleep_latency_us: u32,
+    power_down_latency_us: u32,
+}
+
+impl Policy {
+    /// Create a policy for a timebase with the given frequency
+    ///
+    /// The wake-up latencies default to conservative values of 100 µs for
+    /// deep-sleep mode and 500 µs for power-down mode. Power-down mode is
+    /// allowed by default. The WKT frequency defaults to the nominal 10 kHz of
+    /// the low-power clock, until it's set by [`Idle::new`].
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `timebase_hz` is zero.
+    pub fn new(timebase_hz: u32) -> Self {
+        assert!(timebase_hz != 0);
+
+        Self {
+            timebase_hz,
+            wkt_hz: 10_000,
+            deepest: Mode::PowerDown,
+            deep_sleep_latency_us: 100,
+            power_down_latency_us: 500,
+        }
+    }
+
+    /// Set the frequency of the clock that runs the WKT
+    ///
+    /// [`Idle::new`] sets this to the frequency of the low-power clock the WKT
+    /// runs on, which can be calibrated using [`LowPowerClock::calibrate`].
+    /// Setting it manually is only required, if the policy is used on its own,
+    /// for example in tests.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `hz` is zero.
+    pub fn wkt_frequency(mut self, hz: u32) -> Self {
+        assert!(hz != 0);
+        self.wkt_hz = hz;
+        self
+    }
+
+    /// Set the wake-up latency of deep-sleep mode, in microseconds
+    pub fn deep_sleep_latency(mut self, us: u32) -> Self {
+        self.deep_sleep_latency_us = us;
+        self
+    }
+
+    /// Set the wake-up latency of power-down mode, in microseconds
+    pub fn power_down_latency(mut self, us: u32) -> Self {
+        self.power_down_latency_us = us;
+        self
+    }
+
+    /// Set the deepest sleep mode that may be entered
+    pub fn set_deepest(&mut self, mode: Mode) {
+        self.deepest = mode;
+    }
+
+    /// Returns the deepest sleep mode that may be entered
+    pub fn deepest(&self) -> Mode {
+        self.deepest
+    }
+
+    /// Decide which sleep mode to enter
+    ///
+    /// `now` and `deadline` are in ticks of the timebase.
+    pub fn decide(&self, now: u64, deadline: Option<u64>) -> Decision {
+        let remaining = deadline.map(|deadline| deadline.saturating_sub(now));
+        if remaining == Some(0) {
+            return Decision::Stay;
+        }
+
+        let modes = [
+            (Mode::PowerDown, self.power_down_latency_us),
+            (Mode::DeepSleep, self.deep_sleep_latency_us),
+        ];
+
+        for &(mode, latency_us) in modes.iter() {
+            if mode > self.deepest {
+                continue;
+            }
+
+            let wkt_ticks = match remaining {
+                Some(remaining) => {
+                    let latency = self.us_to_ticks(latency_us);
+                    let available = remaining.saturating_sub(latency);
+                    self.ticks_to_wkt(available)
+                }
+                None => u32::MAX,
+            };
+
+            // The WKT can't be started with zero ticks, and it would not be
+            // worth it to enter a deeper sleep mode for less than one tick.
+            if wkt_ticks == 0 {
+                continue;
+            }
+
+            return match mode {
+                Mode::PowerDown => Decision::PowerDown { wkt_ticks },
+                _ => Decision::DeepSleep { wkt_ticks },
+            };
+        }
+
+        Decision::Sleep
+    }
+
+    /// Convert ticks of the low-power clock into ticks of the timebase
+    pub fn wkt_to_ticks(&self, wkt_ticks: u32) -> u64 {
+        wkt_ticks as u64 * self.timebase_hz as u64 / self.wkt_hz as u64
+    }
+
+    fn ticks_to_wkt(&self, ticks: u64) -> u32 {
+        // Round down, to wake up early rather than late.
+        let wkt_ticks =
+            ticks as u128 * self.wkt_hz as u128 / self.timebase_hz as u128;
+        wkt_ticks.min(u32::MAX as u128) as u32
+    }
+
+    fn us_to_ticks(&self, us: u32) -> u64 {
+        // Round up, to be on the safe side.
+        (us as u64 * self.timebase_hz as u64 + 999_999) / 1_000_000
+    }
+}
+
+/// Tickless idle, based on the PMU and the WKT
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct Idle<'r, 'clock> {
+    policy: Policy,
+    pmu: &'r mut pmu::Handle,
+    scb: &'r mut pac::SCB,
+    syscon: &'r mut syscon::Handle,
+    wkt: &'r mut WKT<init_state::Enabled, &'clock LowPowerClock>,
+}
+
+impl<'r, 'clock> Idle<'r, 'clock> {
+    /// Create an instance of `Idle`
+    ///
+    /// The WKT frequency of `policy` is set to the frequency of the low-power
+    /// clock, which takes any calibration into account (see
+    /// [`LowPowerClock::calibrate`]).
+    ///
+    /// Enables the WKT interrupt as a wake-up source in STARTERP1. Any other
+    /// interrupts that should wake up the microcontroller from deep-sleep and
+    /// power-down modes must be enabled by the user, using
+    /// [`syscon::Handle::enable_interrupt_wakeup`].
+    ///
+    /// [`syscon::Handle::enable_interrupt_wakeup`]: ../syscon/struct.Handle.html#method.enable_interrupt_wakeup
+    pub fn new(
+        policy: Policy,
+        pmu: &'r mut pmu::Handle,
+        scb: &'r mut pac::SCB,
+        syscon: &'r mut syscon::Handle,
+        wkt: &'r mut WKT<init_state::Enabled, &'clock LowPowerClock>,
+    ) -> Self {
+        syscon.enable_interrupt_wakeup::<WktWakeup>();
+
+        Self {
+            policy: policy.wkt_frequency(wkt.hz()),
+            pmu,
+            scb,
+            syscon,
+            wkt,
+        }
+    }
+
+    /// Returns a mutable reference to the policy
+    ///
+    /// Can be used to restrict the deepest sleep mode at runtime.
+    pub fn policy_mut(&mut self) -> &mut Policy {
+        &mut self.policy
+    }
+
+    /// Sleep until the next deadline of `timebase`, or until an interrupt
+    ///
+    /// Enters the sleep mode chosen by the [`Policy`] once, and returns after
+    /// waking up. Interrupt handlers run after the timebase has been
+    /// compensated for the time spent sleeping.
+    pub fn idle<T>(&mut self, timebase: &mut T)
+    where
+        T: Timebase,
+    {
+        let pmu = &mut *self.pmu;
+        let scb = &mut *self.scb;
+        let syscon = &mut *self.syscon;
+        let wkt = &mut *self.wkt;
+        let policy = &self.policy;
+
+        interrupt::free(|_| {
+            let decision =
+                policy.decide(timebase.now(), timebase.next_deadline());
+
+            let (wkt_ticks, power_down) = match decision {
+                Decision::Stay => return,
+                Decision::Sleep => {
+                    pmu.enter_sleep_mode(scb);
+                    return;
+                }
+                Decision::DeepSleep { wkt_ticks } => (wkt_ticks, false),
+                Decision::PowerDown { wkt_ticks } => (wkt_ticks, true),
+            };
+
+            let main_clock = syscon.prepare_deep_sleep();
+
+            wkt.start(wkt_ticks);
+
+            // Safe, because this is not going to interfere with the critical
+            // section.
+            unsafe { NVIC::unmask(Interrupt::WKT) };
+
+            // Sound, as `prepare_deep_sleep` configures PDAWAKECFG to match
+            // the current power configuration.
+            if power_down {
+                unsafe { pmu.enter_power_down_mode(scb) };
+            } else {
+                unsafe { pmu.enter_deep_sleep_mode(scb) };
+            }
+
+            // We might have been woken up early by another interrupt.
+            let slept = if wkt.alarm_occurred() {
+                wkt_ticks
+            } else {
+                wkt_ticks - wkt.value()
+            };
+
+            // The WKT interrupt is only used for waking up. If we don't do
+            // this, the (possibly non-existing) interrupt handler will be
+            // called as soon as we exit this closure.
+            NVIC::mask(Interrupt::WKT);
+            NVIC::unpend(Interrupt::WKT);
+
+            syscon.restore_main_clock(main_clock);
+
+            timebase.compensate(policy.wkt_to_ticks(slept));
+        });
+    }
+}
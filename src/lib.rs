@@ -97,6 +97,9 @@
 
 #![cfg_attr(not(test), no_std)]
 #![deny(missing_docs)]
+// `div_ceil` and `is_multiple_of` are too recent for the Rust versions this
+// crate supports.
+#![allow(clippy::manual_div_ceil, clippy::manual_is_multiple_of)]
 
 pub extern crate cortex_m;
 #[cfg(feature = "rt-selected")]
@@ -118,15 +121,19 @@ pub mod delay;
 pub mod dma;
 pub mod gpio;
 pub mod i2c;
+pub mod idle;
 pub mod mrt;
 #[cfg(feature = "845")]
 pub mod pinint;
 pub mod pins;
 pub mod pmu;
+pub mod sct;
 pub mod sleep;
 pub mod spi;
 pub mod swm;
 pub mod syscon;
+pub mod systick;
+pub mod timer_queue;
 pub mod usart;
 pub mod wkt;
 
@@ -165,6 +172,7 @@ pub use self::mrt::MRT;
 #[cfg(feature = "845")]
 pub use self::pinint::PININT;
 pub use self::pmu::PMU;
+pub use self::sct::SCT;
 pub use self::spi::SPI;
 pub use self::swm::SWM;
 pub use self::syscon::SYSCON;
@@ -280,6 +288,9 @@ pub struct Peripherals {
     /// Power Management Unit
     pub PMU: PMU,
 
+    /// State Configurable Timer (SCT)
+    pub SCT0: SCT<init_state::Disabled>,
+
     /// SPI0
     pub SPI0: SPI<pac::SPI0, init_state::Disabled>,
 
@@ -408,13 +419,6 @@ pub struct Peripherals {
     #[cfg(feature = "82x")]
     pub PININT: pac::PINT,
 
-    /// State Configurable Timer (SCT)
-    ///
-    /// A HAL API for this peripheral has not been implemented yet. In the
-    /// meantime, this field provides you with the raw register mappings, which
-    /// allow you full, unprotected access to the peripheral.
-    pub SCT0: pac::SCT0,
-
     /// Windowed Watchdog Timer (WWDT)
     ///
     /// A HAL API for this peripheral has not been implemented yet. In the
@@ -519,6 +523,7 @@ impl Peripherals {
             #[cfg(feature = "845")]
             PININT: PININT::new(p.PINT),
             PMU: PMU::new(p.PMU),
+            SCT0: SCT::new(p.SCT0),
             SPI0: SPI::new(p.SPI0),
             SPI1: SPI::new(p.SPI1),
             SWM: SWM::new(p.SWM0),
@@ -546,7 +551,6 @@ impl Peripherals {
             IOCON: p.IOCON,
             #[cfg(feature = "82x")]
             PININT: p.PINT,
-            SCT0: p.SCT0,
             WWDT: p.WWDT,
         }
     }
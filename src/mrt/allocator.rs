@@ -0,0 +1,318 @@
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use cortex_m::interrupt;
+use embedded_hal::{
+    blocking::delay::{DelayMs, DelayUs},
+    timer::{CountDown, Periodic},
+};
+use embedded_hal_alpha::{
+    blocking::delay::{DelayMs as DelayMsAlpha, DelayUs as DelayUsAlpha},
+    timer::{CountDown as CountDownAlpha, Periodic as PeriodicAlpha},
+};
+use void::Void;
+
+use crate::pac;
+
+use super::{Channel, Ticks, MRT0, MRT1, MRT2, MRT3};
+
+/// The channels that are currently allocated, one bit per channel
+static ALLOCATED: AtomicU8 = AtomicU8::new(0);
+
+/// Allocates MRT channels at runtime
+///
+/// Use [`MRT::into_allocator`] to gain access to an instance of this struct.
+///
+/// As an alternative to the statically typed channels returned by
+/// [`MRT::split`], the allocator hands out [`AnyChannel`]s, which can be any of
+/// the four channels. This allows independent parts of a program, like
+/// separate driver crates, to share the MRT, without having to agree on which
+/// channel to use beforehand.
+///
+/// `Allocator` can be cloned freely, to pass it to all users of the MRT.
+///
+/// # Example
+///
+/// ``` no_run
+/// use core::convert::TryFrom;
+///
+/// use lpc8xx_hal::{mrt::Ticks, Peripherals};
+///
+/// let p = Peripherals::take().unwrap();
+///
+/// let mut syscon = p.SYSCON.split();
+/// let (allocator, _irq) = p.MRT0.into_allocator(&mut syscon.handle);
+///
+/// let mut timer = allocator.allocate().unwrap();
+/// timer.start_one_shot(Ticks::try_from(1000).unwrap());
+///
+/// // The channel is released, once `timer` is dropped.
+/// drop(timer);
+/// ```
+///
+/// [`MRT::into_allocator`]: super::MRT::into_allocator
+/// [`MRT::split`]: super::MRT::split
+#[derive(Clone)]
+pub struct Allocator {
+    hz: u32,
+}
+
+impl Allocator {
+    pub(super) fn new(hz: u32) -> Self {
+        Self { hz }
+    }
+
+    /// Allocate the lowest free channel
+    ///
+    /// Returns `None`, if all channels are allocated. The channel is stopped,
+    /// and its interrupt is disabled. It is released, once the returned
+    /// `AnyChannel` is dropped.
+    pub fn allocate(&self) -> Option<AnyChannel> {
+        interrupt::free(|_| {
+            // Channels that are not allocated are always stopped. All channels
+            // below the first idle channel are running, which means they must
+            // be allocated already. The search can start at the first idle
+            // channel.
+            let first_idle = mrt().idle_ch.read().chan().bits();
+            let allocated = ALLOCATED.load(Ordering::Relaxed);
+
+            let index =
+                (first_idle..4).find(|i| allocated & (0x1 << i) == 0)?;
+            ALLOCATED.store(allocated | (0x1 << index), Ordering::Relaxed);
+
+            Some(AnyChannel::new(index, self.hz))
+        })
+    }
+
+    /// Returns the number of channels that are not allocated
+    pub fn free_channels(&self) -> u8 {
+        4 - ALLOCATED.load(Ordering::Relaxed).count_ones() as u8
+    }
+}
+
+/// An MRT channel that has been allocated at runtime
+///
+/// Returned by [`Allocator::allocate`]. Provides the same API as [`Channel`],
+/// except that the channel is not known at compile-time.
+///
+/// The channel is stopped and released, once `AnyChannel` is dropped.
+///
+/// # `embedded-hal` traits
+/// - [`embedded_hal::timer::CountDown`]
+/// - [`embedded_hal::blocking::delay::DelayUs`]
+/// - [`embedded_hal::blocking::delay::DelayMs`]
+///
+/// [`embedded_hal::timer::CountDown`]: #impl-CountDown
+/// [`embedded_hal::blocking::delay::DelayUs`]: #impl-DelayUs%3Cu32%3E
+/// [`embedded_hal::blocking::delay::DelayMs`]: #impl-DelayMs%3Cu32%3E
+pub struct AnyChannel(Inner);
+
+enum Inner {
+    Mrt0(Channel<MRT0>),
+    Mrt1(Channel<MRT1>),
+    Mrt2(Channel<MRT2>),
+    Mrt3(Channel<MRT3>),
+}
+
+macro_rules! dispatch {
+    ($self:expr, $channel:ident => $body:expr) => {
+        match $self {
+            Inner::Mrt0($channel) => $body,
+            Inner::Mrt1($channel) => $body,
+            Inner::Mrt2($channel) => $body,
+            Inner::Mrt3($channel) => $body,
+        }
+    };
+}
+
+impl AnyChannel {
+    fn new(index: u8, hz: u32) -> Self {
+        let mut channel = Self(match index {
+            0 => Inner::Mrt0(Channel::new(hz)),
+            1 => Inner::Mrt1(Channel::new(hz)),
+            2 => Inner::Mrt2(Channel::new(hz)),
+            _ => Inner::Mrt3(Channel::new(hz)),
+        });
+
+        channel.reset();
+        channel
+    }
+
+    /// Returns the index of the channel
+    pub fn index(&self) -> u8 {
+        match self.0 {
+            Inner::Mrt0(_) => 0,
+            Inner::Mrt1(_) => 1,
+            Inner::Mrt2(_) => 2,
+            Inner::Mrt3(_) => 3,
+        }
+    }
+
+    /// Start the timer in repeat mode
+    ///
+    /// See [`Channel::start`].
+    pub fn start(&mut self, reload: Ticks) {
+        dispatch!(&mut self.0, c => c.start(reload))
+    }
+
+    /// Start the timer in one-shot mode
+    ///
+    /// See [`Channel::start_one_shot`].
+    pub fn start_one_shot(&mut self, reload: Ticks) {
+        dispatch!(&mut self.0, c => c.start_one_shot(reload))
+    }
+
+    /// Block for the given number of ticks, using bus-stall mode
+    ///
+    /// See [`Channel::delay`].
+    pub fn delay(&mut self, ticks: Ticks) {
+        dispatch!(&mut self.0, c => c.delay(ticks))
+    }
+
+    /// Indicates whether the timer is running
+    pub fn is_running(&self) -> bool {
+        dispatch!(&self.0, c => c.is_running())
+    }
+
+    /// Returns the current timer value
+    pub fn value(&self) -> u32 {
+        dispatch!(&self.0, c => c.value())
+    }
+
+    /// Returns the reload value of the timer
+    pub fn reload_value(&self) -> u32 {
+        dispatch!(&self.0, c => c.reload_value())
+    }
+
+    /// Enable the interrupt of this channel
+    ///
+    /// See [`Channel::enable_interrupt`].
+    pub fn enable_interrupt(&mut self) {
+        dispatch!(&mut self.0, c => c.enable_interrupt())
+    }
+
+    /// Disable the interrupt of this channel
+    pub fn disable_interrupt(&mut self) {
+        dispatch!(&mut self.0, c => c.disable_interrupt())
+    }
+
+    /// Indicates whether the interrupt flag of this channel is set
+    pub fn is_interrupt_pending(&self) -> bool {
+        dispatch!(&self.0, c => c.is_interrupt_pending())
+    }
+
+    /// Clear the interrupt flag of this channel
+    pub fn clear_interrupt(&mut self) {
+        dispatch!(&mut self.0, c => c.clear_interrupt())
+    }
+
+    fn reset(&mut self) {
+        dispatch!(&mut self.0, c => {
+            c.disable_interrupt();
+            c.stop();
+        })
+    }
+}
+
+impl Drop for AnyChannel {
+    fn drop(&mut self) {
+        self.reset();
+
+        let mask = 0x1 << self.index();
+        interrupt::free(|_| {
+            let allocated = ALLOCATED.load(Ordering::Relaxed);
+            ALLOCATED.store(allocated & !mask, Ordering::Relaxed);
+        });
+    }
+}
+
+impl CountDown for AnyChannel {
+    /// The timer operates in clock ticks from the system clock, that means it
+    /// runs at 12_000_000 ticks per second if you haven't changed it.
+    ///
+    /// It can also only use values smaller than 0x7FFFFFFF.
+    type Time = Ticks;
+
+    fn start<Time>(&mut self, count: Time)
+    where
+        Time: Into<Self::Time>,
+    {
+        self.start(count.into());
+    }
+
+    fn wait(&mut self) -> nb::Result<(), Void> {
+        dispatch!(&mut self.0, c => CountDown::wait(c))
+    }
+}
+
+impl CountDownAlpha for AnyChannel {
+    type Error = Void;
+
+    /// The timer operates in clock ticks from the system clock, that means it
+    /// runs at 12_000_000 ticks per second if you haven't changed it.
+    ///
+    /// It can also only use values smaller than 0x7FFFFFFF.
+    type Time = Ticks;
+
+    fn try_start<Time>(&mut self, count: Time) -> Result<(), Self::Error>
+    where
+        Time: Into<Self::Time>,
+    {
+        self.start(count.into());
+        Ok(())
+    }
+
+    fn try_wait(&mut self) -> nb::Result<(), Self::Error> {
+        dispatch!(&mut self.0, c => CountDown::wait(c))
+    }
+}
+
+impl Periodic for AnyChannel {}
+
+impl PeriodicAlpha for AnyChannel {}
+
+macro_rules! delay {
+    ($($ty:ty),*) => {
+        $(
+            impl DelayUs<$ty> for AnyChannel {
+                /// Pauses execution for `us` microseconds
+                fn delay_us(&mut self, us: $ty) {
+                    dispatch!(&mut self.0, c => c.delay_us(us))
+                }
+            }
+
+            impl DelayUsAlpha<$ty> for AnyChannel {
+                type Error = Void;
+
+                /// Pauses execution for `us` microseconds
+                fn try_delay_us(&mut self, us: $ty) -> Result<(), Self::Error> {
+                    self.delay_us(us);
+                    Ok(())
+                }
+            }
+
+            impl DelayMs<$ty> for AnyChannel {
+                /// Pauses execution for `ms` milliseconds
+                fn delay_ms(&mut self, ms: $ty) {
+                    dispatch!(&mut self.0, c => c.delay_ms(ms))
+                }
+            }
+
+            impl DelayMsAlpha<$ty> for AnyChannel {
+                type Error = Void;
+
+                /// Pauses execution for `ms` milliseconds
+                fn try_delay_ms(&mut self, ms: $ty) -> Result<(), Self::Error> {
+                    self.delay_ms(ms);
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+delay!(u32, u16, u8);
+
+fn mrt() -> &'static pac::mrt0::RegisterBlock {
+    // Sound, as IDLE_CH is read-only.
+    unsafe { &*pac::MRT0::ptr() }
+}
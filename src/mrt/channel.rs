@@ -1,68 +1,183 @@
-use crate::reg_proxy::{Reg, RegProxy};
+use crate::{
+    delay::{self, impl_delay, Blocking},
+    reg_proxy::{Reg, RegProxy},
+};
 
-use embedded_hal::timer::{CountDown, Periodic};
-use embedded_hal_alpha::timer::{
-    CountDown as CountDownAlpha, Periodic as PeriodicAlpha,
+use embedded_hal::{
+    blocking::delay::DelayUs,
+    timer::{CountDown, Periodic},
+};
+use embedded_hal_alpha::{
+    blocking::delay::DelayUs as DelayUsAlpha,
+    timer::{CountDown as CountDownAlpha, Periodic as PeriodicAlpha},
 };
 use embedded_time::{clock, fraction::Fraction, Instant};
 use void::Void;
 
-use super::{Ticks, Trait};
+use super::{Ticks, Trait, MAX_VALUE};
 
 /// Represents a MRT0 channel
 ///
+/// The blocking delays of the [`DelayUs`] and [`DelayMs`] implementations run
+/// the timer in one-shot mode and poll it until it has finished, so interrupts
+/// are still serviced while waiting. They convert durations to ticks using the
+/// clock frequency declared via [`MRT::set_clock_frequency`]. For a delay that
+/// stalls the bus instead, use [`Channel::delay`] or [`Channel::bus_stall`].
+///
 /// # `embedded-hal` traits
 /// - [`embedded_hal::timer::CountDown`]
+/// - [`embedded_hal::blocking::delay::DelayUs`]
+/// - [`embedded_hal::blocking::delay::DelayMs`]
 ///
 /// [`embedded_hal::timer::CountDown`]: #impl-CountDown
-pub struct Channel<T: Reg>(RegProxy<T>);
+/// [`embedded_hal::blocking::delay::DelayUs`]: #impl-DelayUs%3Cu32%3E
+/// [`embedded_hal::blocking::delay::DelayMs`]: #impl-DelayMs%3Cu32%3E
+/// [`DelayMs`]: embedded_hal::blocking::delay::DelayMs
+/// [`MRT::set_clock_frequency`]: super::MRT::set_clock_frequency
+pub struct Channel<T: Reg> {
+    regs: RegProxy<T>,
+    hz: u32,
+}
 
 impl<T> Channel<T>
 where
     T: Trait,
 {
-    pub(super) fn new() -> Self {
-        Self(RegProxy::new())
+    pub(super) fn new(hz: u32) -> Self {
+        Self {
+            regs: RegProxy::new(),
+            hz,
+        }
     }
 
-    /// Start the timer
+    /// Start the timer in repeat mode
+    ///
+    /// The timer restarts automatically, each time it reaches zero.
     ///
     /// The `reload` argument must be smaller than or equal to [`MAX_VALUE`].
     ///
     /// [`MAX_VALUE`]: constant.MAX_VALUE.html
     pub fn start(&mut self, reload: Ticks) {
-        // This stops the timer, to prevent race conditions when resetting the
-        // interrupt bit
-        self.0.intval.write(|w| {
-            w.load().set_bit();
-            unsafe { w.ivalue().bits(0) }
-        });
-        self.0.stat.write(|w| w.intflag().set_bit());
-        self.0
-            .intval
-            .write(|w| unsafe { w.ivalue().bits(reload.0 + 1) });
+        self.start_in_mode(reload, Mode::Repeat);
+    }
+
+    /// Start the timer in one-shot mode
+    ///
+    /// The timer stops, once it reaches zero. Apart from that, it works just
+    /// like in repeat mode. The end of the count down can be detected using the
+    /// [`CountDown`] implementation, or the channel's interrupt.
+    ///
+    /// The `reload` argument must be smaller than or equal to [`MAX_VALUE`].
+    ///
+    /// [`MAX_VALUE`]: constant.MAX_VALUE.html
+    pub fn start_one_shot(&mut self, reload: Ticks) {
+        self.start_in_mode(reload, Mode::OneShot);
+    }
+
+    /// Block for the given number of ticks, using bus-stall mode
+    ///
+    /// In bus-stall mode, the write to the timer's interval register is
+    /// stalled until the timer reaches zero. This stalls the CPU for a precise
+    /// amount of time, without any polling. Please note that no interrupts are
+    /// serviced during that time, and that all other bus masters, like DMA,
+    /// are stalled too, if they try to access the bus.
+    ///
+    /// The timer is stopped when this method returns.
+    pub fn delay(&mut self, ticks: Ticks) {
+        self.stop();
+        self.regs.ctrl.modify(|_, w| w.mode().one_shot_stall_mode());
+
+        if ticks.0 != 0 {
+            // This write only completes, once the timer has reached zero.
+            self.regs
+                .intval
+                .write(|w| unsafe { w.ivalue().bits(ticks.0) });
+        }
+
+        self.regs.stat.write(|w| w.intflag().set_bit());
+    }
+
+    /// Use bus-stall mode for the delays of this channel
+    ///
+    /// Returns an adapter that implements [`DelayUs`] using
+    /// [`Channel::delay`], for delays with sub-microsecond precision.
+    pub fn bus_stall(&mut self) -> BusStall<'_, T> {
+        BusStall(self)
+    }
+
+    /// Returns the declared frequency of the MRT clock
+    #[cfg(feature = "rtic-monotonic")]
+    pub(super) fn hz(&self) -> u32 {
+        self.hz
     }
 
     /// Indicates whether the timer is running
     pub fn is_running(&self) -> bool {
-        self.0.stat.read().run().is_running()
+        self.regs.stat.read().run().is_running()
     }
 
     /// Returns the current timer value
     pub fn value(&self) -> u32 {
-        self.0.timer.read().value().bits()
+        self.regs.timer.read().value().bits()
     }
 
     /// Returns the reload value of the timer
     pub fn reload_value(&self) -> u32 {
-        self.0.intval.read().ivalue().bits()
+        self.regs.intval.read().ivalue().bits()
+    }
+
+    fn start_in_mode(&mut self, reload: Ticks, mode: Mode) {
+        self.stop();
+        self.regs.ctrl.modify(|_, w| match mode {
+            Mode::Repeat => w.mode().repeat_interrupt_mode(),
+            Mode::OneShot => w.mode().one_shot_interrupt_mode(),
+        });
+        self.regs
+            .intval
+            .write(|w| unsafe { w.ivalue().bits(reload.0 + 1) });
+    }
+
+    pub(super) fn stop(&mut self) {
+        // This stops the timer, to prevent race conditions when resetting the
+        // interrupt bit
+        self.regs.intval.write(|w| {
+            w.load().set_bit();
+            unsafe { w.ivalue().bits(0) }
+        });
+        self.regs.stat.write(|w| w.intflag().set_bit());
+    }
+
+    /// Enable the interrupt of this channel
+    ///
+    /// The interrupt is requested, once the timer reaches zero. All channels
+    /// share a single interrupt, which needs to be enabled in the NVIC too,
+    /// using [`Irq::enable_in_nvic`].
+    ///
+    /// [`Irq::enable_in_nvic`]: super::Irq::enable_in_nvic
+    pub fn enable_interrupt(&mut self) {
+        self.regs.ctrl.modify(|_, w| w.inten().enabled());
+    }
+
+    /// Disable the interrupt of this channel
+    pub fn disable_interrupt(&mut self) {
+        self.regs.ctrl.modify(|_, w| w.inten().disabled());
+    }
+
+    /// Indicates whether the interrupt flag of this channel is set
+    pub fn is_interrupt_pending(&self) -> bool {
+        self.regs.stat.read().intflag().is_pending_interrupt()
+    }
+
+    /// Clear the interrupt flag of this channel
+    pub fn clear_interrupt(&mut self) {
+        self.regs.stat.write(|w| w.intflag().set_bit());
     }
 
     /// Non-blockingly "waits" until the count down finishes
     fn wait(&mut self) -> nb::Result<(), Void> {
-        if self.0.stat.read().intflag().is_pending_interrupt() {
+        if self.regs.stat.read().intflag().is_pending_interrupt() {
             // Reset the interrupt flag
-            self.0.stat.write(|w| w.intflag().set_bit());
+            self.regs.stat.write(|w| w.intflag().set_bit());
             Ok(())
         } else {
             Err(nb::Error::WouldBlock)
@@ -116,6 +231,87 @@ where
     }
 }
 
+impl<T> Blocking for Channel<T>
+where
+    T: Trait,
+{
+    fn ticks_per_second(&self) -> u32 {
+        self.hz
+    }
+
+    // Runs the timer in one-shot mode and polls the interrupt flag, so
+    // interrupts can be serviced while waiting.
+    fn block(&mut self, mut ticks: u64) {
+        // Don't request the interrupt while waiting, so no interrupt handler
+        // can clear the flag before we see it.
+        let interrupt_enabled = self.regs.ctrl.read().inten().is_enabled();
+        self.disable_interrupt();
+
+        while ticks != 0 {
+            let chunk = ticks.min(MAX_VALUE.0 as u64 + 1);
+            self.start_one_shot(Ticks(chunk as u32 - 1));
+            while !self.is_interrupt_pending() {}
+            ticks -= chunk;
+        }
+
+        self.clear_interrupt();
+        if interrupt_enabled {
+            self.enable_interrupt();
+        }
+    }
+}
+
+impl_delay!([T] Channel<T>, [T: Trait]);
+
+/// Implements [`DelayUs`] for a channel, using bus-stall mode
+///
+/// Returned by [`Channel::bus_stall`]. Please refer to [`Channel::delay`] for
+/// the implications of bus-stall mode.
+///
+/// # `embedded-hal` traits
+/// - [`embedded_hal::blocking::delay::DelayUs`]
+///
+/// [`embedded_hal::blocking::delay::DelayUs`]: #impl-DelayUs%3Cu32%3E
+pub struct BusStall<'a, T: Reg>(&'a mut Channel<T>);
+
+macro_rules! delay_bus_stall {
+    ($($ty:ty),*) => {
+        $(
+            impl<T> DelayUs<$ty> for BusStall<'_, T>
+            where
+                T: Trait,
+            {
+                /// Pauses execution for `us` microseconds, using bus-stall mode
+                fn delay_us(&mut self, us: $ty) {
+                    let hz = self.0.hz;
+                    let mut ticks = delay::ticks(us.into(), 1_000_000, hz);
+
+                    while ticks != 0 {
+                        let chunk = ticks.min(MAX_VALUE.0 as u64);
+                        self.0.delay(Ticks(chunk as u32));
+                        ticks -= chunk;
+                    }
+                }
+            }
+
+            impl<T> DelayUsAlpha<$ty> for BusStall<'_, T>
+            where
+                T: Trait,
+            {
+                type Error = Void;
+
+                /// Pauses execution for `us` microseconds, using bus-stall mode
+                fn try_delay_us(&mut self, us: $ty) -> Result<(), Self::Error> {
+                    self.delay_us(us);
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+delay_bus_stall!(u32, u16, u8);
+
 impl<T> Periodic for Channel<T> where T: Trait {}
 
 impl<T> PeriodicAlpha for Channel<T> where T: Trait {}
@@ -138,3 +334,8 @@ where
         }
     }
 }
+
+enum Mode {
+    Repeat,
+    OneShot,
+}
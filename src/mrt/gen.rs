@@ -3,10 +3,13 @@ use crate::{
     reg_proxy::Reg,
 };
 
-use super::Channel;
+use super::{Channel, Irq};
 
 /// Implemented for types that identify MRT channels
-pub trait Trait: Reg<Target = CHANNEL> + sealed::Sealed {}
+pub trait Trait: Reg<Target = CHANNEL> + sealed::Sealed {
+    /// The index of the channel
+    const INDEX: u8;
+}
 
 macro_rules! channels {
     ($($channel:ident, $field:ident, $index:expr;)*) => {
@@ -16,12 +19,16 @@ macro_rules! channels {
                 #[allow(missing_docs)]
                 pub $field: Channel<$channel>,
             )*
+
+            /// The MRT interrupt, which is shared by all channels
+            pub irq: Irq,
         }
 
         impl Channels {
-            pub(super) fn new() -> Self {
+            pub(super) fn new(hz: u32) -> Self {
                 Self {
-                    $($field: Channel::new(),)*
+                    $($field: Channel::new(hz),)*
+                    irq: Irq::new(),
                 }
             }
         }
@@ -35,7 +42,9 @@ macro_rules! channels {
             reg_cluster_array!($channel, CHANNEL, pac::MRT0, channel, $index);
 
             impl sealed::Sealed for $channel {}
-            impl Trait for $channel {}
+            impl Trait for $channel {
+                const INDEX: u8 = $index;
+            }
         )*
     }
 }
@@ -0,0 +1,141 @@
+use crate::pac::{self, Interrupt, NVIC};
+
+use super::{AnyChannel, Trait};
+
+/// Provides access to the MRT interrupt, which is shared by all channels
+///
+/// Can be accessed via [`Channels`]. The interrupt of each channel is enabled
+/// using [`Channel::enable_interrupt`]. From the interrupt handler, use
+/// [`Irq::pending`] to find out which channels have fired.
+///
+/// # Example
+///
+/// ``` no_run
+/// use lpc8xx_hal::mrt::Irq;
+///
+/// fn handle_interrupt(irq: &mut Irq) {
+///     let pending = irq.pending();
+///
+///     if pending.mrt0() {
+///         // channel 0 has fired
+///     }
+///     if pending.mrt1() {
+///         // channel 1 has fired
+///     }
+/// }
+/// ```
+///
+/// [`Channels`]: super::Channels
+/// [`Channel::enable_interrupt`]: super::Channel::enable_interrupt
+pub struct Irq(());
+
+impl Irq {
+    pub(super) fn new() -> Self {
+        Self(())
+    }
+
+    /// Returns the channels that have a pending interrupt, and clears them
+    ///
+    /// Reads the global interrupt flag register once and clears exactly the
+    /// flags that were read. Flags that are set after the read are not
+    /// affected, and will be reported on the next call.
+    ///
+    /// Only channels that have their interrupt enabled are reported and
+    /// cleared. The flags of all other channels are left alone, so they can
+    /// still be polled, for example by the [`CountDown`] implementation of the
+    /// channels.
+    ///
+    /// [`CountDown`]: embedded_hal::timer::CountDown
+    pub fn pending(&mut self) -> Pending {
+        let mrt = mrt();
+
+        let mut enabled = 0;
+        for (i, channel) in mrt.channel.iter().enumerate() {
+            if channel.ctrl.read().inten().is_enabled() {
+                enabled |= 0x1 << i;
+            }
+        }
+
+        let flags = mrt.irq_flag.read().bits() as u8 & enabled;
+        // Sound, as writing ones to the flags just clears them.
+        mrt.irq_flag.write(|w| unsafe { w.bits(flags as u32) });
+
+        Pending(flags)
+    }
+
+    /// Enable the MRT interrupt in the NVIC
+    ///
+    /// This only enables the interrupt in the NVIC. It doesn't enable the
+    /// interrupt of any channel.
+    pub fn enable_in_nvic(&mut self) {
+        // Safe, because there's no critical section here that this could
+        // interfere with.
+        unsafe { NVIC::unmask(Interrupt::MRT0) };
+    }
+
+    /// Disable the MRT interrupt in the NVIC
+    ///
+    /// This only disables the interrupt in the NVIC. It doesn't change anything
+    /// about the interrupt configuration of the channels.
+    pub fn disable_in_nvic(&mut self) {
+        NVIC::mask(Interrupt::MRT0);
+    }
+
+    /// Clear the MRT interrupt's pending flag in the NVIC
+    ///
+    /// This only clears the interrupt's pending flag in the NVIC. It does not
+    /// affect any of the interrupt flags of the channels.
+    pub fn clear_nvic_pending(&mut self) {
+        NVIC::unpend(Interrupt::MRT0);
+    }
+}
+
+/// The channels that had a pending interrupt
+///
+/// Returned by [`Irq::pending`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Pending(u8);
+
+impl Pending {
+    /// Indicates whether channel `T` had a pending interrupt
+    pub fn contains<T: Trait>(&self) -> bool {
+        self.0 & 0x1 << T::INDEX != 0
+    }
+
+    /// Indicates whether the given allocated channel had a pending interrupt
+    pub fn contains_channel(&self, channel: &AnyChannel) -> bool {
+        self.0 & 0x1 << channel.index() != 0
+    }
+
+    /// Indicates whether no channel had a pending interrupt
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Indicates whether channel 0 had a pending interrupt
+    pub fn mrt0(&self) -> bool {
+        self.0 & 0x1 != 0
+    }
+
+    /// Indicates whether channel 1 had a pending interrupt
+    pub fn mrt1(&self) -> bool {
+        self.0 & 0x2 != 0
+    }
+
+    /// Indicates whether channel 2 had a pending interrupt
+    pub fn mrt2(&self) -> bool {
+        self.0 & 0x4 != 0
+    }
+
+    /// Indicates whether channel 3 had a pending interrupt
+    pub fn mrt3(&self) -> bool {
+        self.0 & 0x8 != 0
+    }
+}
+
+fn mrt() -> &'static pac::mrt0::RegisterBlock {
+    // Sound, as `Irq` is the only user of IRQ_FLAG, and only writes ones to
+    // it, which has the same effect as the channels clearing their own flags.
+    // Apart from that, it only reads the channels' CTRL registers.
+    unsafe { &*pac::MRT0::ptr() }
+}
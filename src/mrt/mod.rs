@@ -1,19 +1,33 @@
 //! API for the MRT (Multi-Rate Timer) peripheral
 //!
 //! Please be aware that this doesn't try to abstract everything, it only
-//! implements the embedded-hal `Timer` functionality.
+//! implements the embedded-hal `Timer` and `DelayUs` functionality.
 //!
 //! The MRT consists of 4 channels, which are mostly separate and can each act
-//! as a run-of-the-mill timer.
+//! as a run-of-the-mill timer. Each channel can run in repeat mode
+//! ([`Channel::start`]), one-shot mode ([`Channel::start_one_shot`]), or
+//! one-shot bus-stall mode ([`Channel::delay`], [`Channel::bus_stall`]).
+//!
+//! The channels can either be accessed as statically typed channels, using
+//! [`MRT::split`], or allocated at runtime, using [`MRT::into_allocator`].
 
+mod allocator;
 mod channel;
 mod gen;
+mod irq;
+#[cfg(feature = "rtic-monotonic")]
+mod monotonic;
 mod peripheral;
 mod ticks;
 
+#[cfg(feature = "rtic-monotonic")]
+pub use self::monotonic::Monotonic;
+
 pub use self::{
-    channel::Channel,
+    allocator::{Allocator, AnyChannel},
+    channel::{BusStall, Channel},
     gen::*,
+    irq::{Irq, Pending},
     peripheral::MRT,
     ticks::{TickConversionError, Ticks},
 };
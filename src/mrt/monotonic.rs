@@ -0,0 +1,155 @@
+use embedded_time::{clock, fraction::Fraction, Instant};
+
+use super::{Channel, Ticks, Trait, MAX_VALUE};
+
+/// An RTIC monotonic timer, based on two MRT channels
+///
+/// One channel serves as the timebase. It runs continuously in repeat mode,
+/// and its overflows are counted in software, which extends it to 64 bits. The
+/// other channel is used in one-shot mode to generate the compare interrupt.
+///
+/// Both channels request the shared MRT interrupt, which needs to be bound to
+/// the monotonic. The interrupts of the other MRT channels can't be used at
+/// the same time. The timebase overflows about every 179 seconds at 12 MHz, so
+/// the interrupt stays enabled, even if no tasks are scheduled.
+///
+/// `HZ` must be the frequency of the clock that drives the MRT, as declared
+/// using [`MRT::set_clock_frequency`].
+///
+/// Only available, if the `rtic-monotonic` feature is enabled.
+///
+/// # Example
+///
+/// ``` ignore
+/// #[rtic::app(device = lpc8xx_hal::pac)]
+/// mod app {
+///     use lpc8xx_hal::mrt::{self, MRT0, MRT1};
+///
+///     #[monotonic(binds = MRT0, default = true)]
+///     type Mono = mrt::Monotonic<MRT0, MRT1, 12_000_000>;
+///
+///     #[init]
+///     fn init(cx: init::Context) -> (Shared, Local, init::Monotonics) {
+///         // ...
+///         let mrt = p.MRT0.split(&mut syscon.handle);
+///         let mono = mrt::Monotonic::new(mrt.mrt0, mrt.mrt1);
+///
+///         (Shared {}, Local {}, init::Monotonics(mono))
+///     }
+/// }
+/// ```
+///
+/// [`MRT::set_clock_frequency`]: super::MRT::set_clock_frequency
+pub struct Monotonic<T: Trait, C: Trait, const HZ: u32> {
+    timebase: Channel<T>,
+    compare: Channel<C>,
+    overflows: u64,
+}
+
+impl<T, C, const HZ: u32> Monotonic<T, C, HZ>
+where
+    T: Trait,
+    C: Trait,
+{
+    /// Create the monotonic timer and start the timebase
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `HZ` doesn't match the clock frequency declared using
+    /// [`MRT::set_clock_frequency`].
+    ///
+    /// [`MRT::set_clock_frequency`]: super::MRT::set_clock_frequency
+    pub fn new(mut timebase: Channel<T>, mut compare: Channel<C>) -> Self {
+        assert_eq!(timebase.hz(), HZ);
+
+        timebase.start(MAX_VALUE);
+        timebase.enable_interrupt();
+        compare.enable_interrupt();
+
+        Self {
+            timebase,
+            compare,
+            overflows: 0,
+        }
+    }
+
+    /// Release the MRT channels
+    pub fn free(mut self) -> (Channel<T>, Channel<C>) {
+        self.timebase.disable_interrupt();
+        self.compare.disable_interrupt();
+
+        (self.timebase, self.compare)
+    }
+
+    fn ticks(&self) -> u64 {
+        // The number of ticks per timebase period. This matches the interval
+        // that `Channel::start` configures for `MAX_VALUE`.
+        const PERIOD: u64 = MAX_VALUE.0 as u64 + 1;
+
+        // An overflow that hasn't been counted yet is indicated by the
+        // interrupt flag. Make sure the timer value is consistent with the
+        // flag, in case an overflow happens while reading.
+        loop {
+            let before = self.timebase.is_interrupt_pending();
+            let value = self.timebase.value() as u64;
+            let after = self.timebase.is_interrupt_pending();
+
+            if before == after {
+                let overflows = self.overflows + after as u64;
+                return overflows * PERIOD + (PERIOD - 1 - value);
+            }
+        }
+    }
+}
+
+impl<T, C, const HZ: u32> embedded_time::Clock for Monotonic<T, C, HZ>
+where
+    T: Trait,
+    C: Trait,
+{
+    type T = u64;
+
+    const SCALING_FACTOR: Fraction = Fraction::new(1, HZ);
+
+    fn try_now(&self) -> Result<Instant<Self>, clock::Error> {
+        Ok(Instant::new(self.ticks()))
+    }
+}
+
+impl<T, C, const HZ: u32> rtic_monotonic::Monotonic for Monotonic<T, C, HZ>
+where
+    T: Trait,
+    C: Trait,
+{
+    // Overflows of the timebase need to be counted, even if no tasks are
+    // scheduled.
+    const DISABLE_INTERRUPT_ON_EMPTY_QUEUE: bool = false;
+
+    unsafe fn reset(&mut self) {
+        self.timebase.start(MAX_VALUE);
+        self.overflows = 0;
+    }
+
+    fn set_compare(&mut self, instant: &Instant<Self>) {
+        let now = self.ticks();
+        let target = instant.duration_since_epoch().integer();
+
+        // If the instant is in the past, fire as soon as possible. If it's too
+        // far in the future, fire early. RTIC will set the compare value again.
+        let delta = target.saturating_sub(now).max(1) - 1;
+        let delta = delta.min(MAX_VALUE.0 as u64) as u32;
+
+        self.compare.start_one_shot(Ticks(delta));
+    }
+
+    fn clear_compare_flag(&mut self) {
+        self.compare.clear_interrupt();
+    }
+
+    fn on_interrupt(&mut self) {
+        if self.timebase.is_interrupt_pending() {
+            self.timebase.clear_interrupt();
+            self.overflows += 1;
+        }
+    }
+}
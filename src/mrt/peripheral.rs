@@ -1,10 +1,13 @@
-use super::Channels;
+use super::{Allocator, Channels, Irq};
+
+use embedded_time::rate::Hertz;
 
 use crate::{pac, syscon};
 
 /// Represents the MRT instance
 pub struct MRT {
     mrt: pac::MRT0,
+    hz: u32,
 }
 
 impl MRT {
@@ -14,14 +17,48 @@ impl MRT {
     /// - Each channel is in repeat mode
     /// - All channel interrupts are disabled
     pub(crate) fn new(mrt: pac::MRT0) -> Self {
-        Self { mrt }
+        Self {
+            mrt,
+            hz: 12_000_000,
+        }
+    }
+
+    /// Declare the frequency of the clock that drives the MRT
+    ///
+    /// The MRT runs on the system clock. Unless told otherwise, it assumes the
+    /// system clock runs at 12 MHz, its frequency after reset. If you configure
+    /// a different system clock, declare its frequency here, before splitting
+    /// the MRT. The channels use it to convert the durations passed to their
+    /// blocking delays into ticks.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `frequency` is zero.
+    pub fn set_clock_frequency(&mut self, frequency: impl Into<Hertz>) {
+        let frequency = frequency.into().0;
+        assert!(frequency != 0);
+        self.hz = frequency;
     }
 
     /// Enables the MRT and splits it into it's four channels
     pub fn split(self, syscon: &mut syscon::Handle) -> Channels {
         syscon.enable_clock(&self.mrt);
 
-        Channels::new()
+        Channels::new(self.hz)
+    }
+
+    /// Enables the MRT for runtime allocation of its channels
+    ///
+    /// This is an alternative to [`MRT::split`], for cases where it's not
+    /// known at compile-time which channel should be used for which purpose.
+    /// See [`Allocator`] for details.
+    pub fn into_allocator(
+        self,
+        syscon: &mut syscon::Handle,
+    ) -> (Allocator, Irq) {
+        syscon.enable_clock(&self.mrt);
+
+        (Allocator::new(self.hz), Irq::new())
     }
 
     /// Return the raw peripheral
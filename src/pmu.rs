@@ -30,8 +30,15 @@
 //! [examples in the repository]: https://github.com/lpc-rs/lpc8xx-hal/tree/master/examples
 
 use cortex_m::{asm, interrupt};
+use embedded_hal::timer::CountDown as _;
+#[cfg(feature = "82x")]
+use embedded_time::rate::Hertz;
 
-use crate::{clock, init_state, pac};
+use crate::{
+    clock, init_state, pac,
+    pins::{self, Pin, PIO0_28, PIO0_4},
+    wkt::WKT,
+};
 
 /// Entry point to the PMU API
 ///
@@ -122,12 +129,7 @@ impl Handle {
     /// interrupt occurs. See user manual, section 6.7.4.3.
     pub fn enter_sleep_mode(&mut self, scb: &mut pac::SCB) {
         interrupt::free(|_| {
-            // Default power mode indicates active or sleep mode.
-            self.pmu.pcon.modify(|_, w| w.pm().default());
-
-            // The SLEEPDEEP bit must be cleared when entering regular sleep
-            // mode. See user manual, section 6.7.4.2.
-            scb.clear_sleepdeep();
+            self.select_power_mode(scb, PowerMode::sleep());
 
             asm::dsb();
             asm::wfi();
@@ -161,11 +163,7 @@ impl Handle {
     /// method.
     pub unsafe fn enter_deep_sleep_mode(&mut self, scb: &mut pac::SCB) {
         interrupt::free(|_| {
-            self.pmu.pcon.modify(|_, w| w.pm().deep_sleep_mode());
-
-            // The SLEEPDEEP bit must be set for entering regular sleep mode.
-            // See user manual, section 6.7.5.2.
-            scb.set_sleepdeep();
+            self.select_power_mode(scb, PowerMode::deep_sleep());
 
             asm::dsb();
             asm::wfi();
@@ -199,34 +197,671 @@ impl Handle {
     /// method.
     pub unsafe fn enter_power_down_mode(&mut self, scb: &mut pac::SCB) {
         interrupt::free(|_| {
-            self.pmu.pcon.modify(|_, w| w.pm().power_down_mode());
+            self.select_power_mode(scb, PowerMode::power_down());
+
+            asm::dsb();
+            asm::wfi();
+        })
+    }
+
+    /// Sleep automatically, whenever an interrupt handler returns
+    ///
+    /// Sets the SLEEPONEXIT bit, which causes the core to enter the given power
+    /// mode whenever it returns from an interrupt handler to thread mode. This
+    /// is useful for firmware that does all of its work in interrupt handlers.
+    /// The main function only needs to call [`Handle::wait_for_interrupt`]
+    /// once after initialization, to enter the power mode for the first time.
+    ///
+    /// The power mode can be changed later, by calling this method again from
+    /// an interrupt handler. See user manual, section 6.7.1.
+    ///
+    /// # Example
+    ///
+    /// ``` no_run
+    /// use lpc8xx_hal::{pac::CorePeripherals, pmu::PowerMode, Peripherals};
+    ///
+    /// let mut cp = CorePeripherals::take().unwrap();
+    /// let p = Peripherals::take().unwrap();
+    ///
+    /// let mut pmu = p.PMU.split();
+    ///
+    /// // Set up interrupts here.
+    ///
+    /// pmu.handle.enable_sleep_on_exit(&mut cp.SCB, PowerMode::sleep());
+    /// loop {
+    ///     // Only returns, once sleep-on-exit has been disabled.
+    ///     pmu.handle.wait_for_interrupt(&mut cp.SCB, PowerMode::sleep());
+    /// }
+    /// ```
+    pub fn enable_sleep_on_exit(
+        &mut self,
+        scb: &mut pac::SCB,
+        mode: PowerMode,
+    ) {
+        interrupt::free(|_| {
+            self.select_power_mode(scb, mode);
+            scb.set_sleeponexit();
+        })
+    }
+
+    /// Stop sleeping automatically, whenever an interrupt handler returns
+    ///
+    /// Clears the SLEEPONEXIT bit. If called from an interrupt handler, the
+    /// core returns to the code that was interrupted, which is usually the
+    /// call to [`Handle::wait_for_interrupt`] in the main function.
+    pub fn disable_sleep_on_exit(&mut self, scb: &mut pac::SCB) {
+        scb.clear_sleeponexit();
+    }
+
+    /// Enter the given power mode, until an interrupt occurs
+    ///
+    /// Unlike [`Handle::enter_sleep_mode`] and the other methods that enter a
+    /// specific power mode, this method doesn't mask interrupts. If an
+    /// interrupt wakes up the core, its handler runs before this method
+    /// returns.
+    pub fn wait_for_interrupt(&mut self, scb: &mut pac::SCB, mode: PowerMode) {
+        interrupt::free(|_| self.select_power_mode(scb, mode));
 
-            // The SLEEPDEEP bit must be set for entering regular sleep mode.
-            // See user manual, section 6.7.5.2.
+        asm::dsb();
+        asm::wfi();
+    }
+
+    /// Configure whether pending interrupts are events
+    ///
+    /// Sets or clears the SEVONPEND bit. If set, any interrupt that becomes
+    /// pending wakes up the core from [`Handle::wait_for_event`], even if it
+    /// is disabled in the NVIC or masked by PRIMASK. This allows event-driven
+    /// firmware to poll the NVIC after waking up, without running any
+    /// interrupt handlers.
+    ///
+    /// Only interrupts that become pending after the core went to sleep count
+    /// as events. Interrupts that were already pending before don't.
+    pub fn set_event_on_pending(&mut self, scb: &mut pac::SCB, enable: bool) {
+        // Sound, as we only change the SEVONPEND bit, and have exclusive
+        // access to the SCB.
+        unsafe {
+            scb.scr.modify(|scr| {
+                if enable {
+                    scr | SCB_SCR_SEVONPEND
+                } else {
+                    scr & !SCB_SCR_SEVONPEND
+                }
+            })
+        }
+    }
+
+    /// Enter the given power mode, until an event occurs
+    ///
+    /// Executes the WFE instruction. The core wakes up if an event is
+    /// signaled, for example by the SEV instruction, by an enabled interrupt,
+    /// or by any interrupt that becomes pending, if configured using
+    /// [`Handle::set_event_on_pending`].
+    ///
+    /// If an event was signaled since the last call, this method returns
+    /// immediately, as the event is latched by the core. It should therefore
+    /// be called in a loop that checks whether there's any work to do.
+    ///
+    /// # Example
+    ///
+    /// ``` no_run
+    /// use lpc8xx_hal::{
+    ///     pac::{CorePeripherals, Interrupt, NVIC},
+    ///     pmu::PowerMode,
+    ///     Peripherals,
+    /// };
+    ///
+    /// let mut cp = CorePeripherals::take().unwrap();
+    /// let p = Peripherals::take().unwrap();
+    ///
+    /// let mut pmu = p.PMU.split();
+    /// pmu.handle.set_event_on_pending(&mut cp.SCB, true);
+    ///
+    /// loop {
+    ///     pmu.handle.wait_for_event(&mut cp.SCB, PowerMode::sleep());
+    ///
+    ///     // The interrupt is not enabled in the NVIC, so it stays pending
+    ///     // until we handle it here.
+    ///     if NVIC::is_pending(Interrupt::WKT) {
+    ///         NVIC::unpend(Interrupt::WKT);
+    ///         // handle the event
+    ///     }
+    /// }
+    /// ```
+    pub fn wait_for_event(&mut self, scb: &mut pac::SCB, mode: PowerMode) {
+        interrupt::free(|_| self.select_power_mode(scb, mode));
+
+        asm::dsb();
+        asm::wfe();
+    }
+
+    /// Select the power mode that is entered by the WFI and WFE instructions
+    fn select_power_mode(&mut self, scb: &mut pac::SCB, mode: PowerMode) {
+        match mode.0 {
+            Pm::Sleep => {
+                // Default power mode indicates active or sleep mode.
+                self.pmu.pcon.modify(|_, w| w.pm().default());
+
+                // The SLEEPDEEP bit must be cleared when entering regular
+                // sleep mode. See user manual, section 6.7.4.2.
+                scb.clear_sleepdeep();
+            }
+            Pm::DeepSleep | Pm::PowerDown => {
+                self.pmu.pcon.modify(|_, w| match mode.0 {
+                    Pm::DeepSleep => w.pm().deep_sleep_mode(),
+                    _ => w.pm().power_down_mode(),
+                });
+
+                // The SLEEPDEEP bit must be set for entering deep-sleep and
+                // power-down modes. See user manual, sections 6.7.5.2 and
+                // 6.7.6.2.
+                scb.set_sleepdeep();
+            }
+        }
+    }
+
+    /// Enter deep power-down mode
+    ///
+    /// In deep power-down mode, power is shut off to the entire chip, except
+    /// for the WAKEUP pin and the self-wake-up timer (WKT). Waking up from this
+    /// mode causes a reset, which means this method never returns. Use
+    /// [`Handle::woke_from_deep_power_down`] after the reset, to find out
+    /// whether the microcontroller woke up from deep power-down mode. See user
+    /// manual, section 6.7.7.
+    ///
+    /// `wakeup_pin` configures the WAKEUP pin. If `wkt` is `Some`, the WKT is
+    /// started with the given number of ticks of the low-power clock, and the
+    /// low-power oscillator is configured to keep running in deep power-down
+    /// mode. The WKT must run on the low-power clock, as it's the only clock
+    /// that keeps running.
+    ///
+    /// Data can be retained in the general-purpose registers (GPREG0-3), while
+    /// the microcontroller is in deep power-down mode. See
+    /// [`Handle::store_retained`].
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the WAKEUP pin is disabled and `wkt` is `None`, as the
+    /// microcontroller could then only be woken up by a power-on reset.
+    pub fn enter_deep_power_down(
+        &mut self,
+        scb: &mut pac::SCB,
+        wakeup_pin: WakeupPin,
+        wkt: Option<(&mut WKT<init_state::Enabled, &LowPowerClock>, u32)>,
+    ) -> ! {
+        assert!(!matches!(wakeup_pin, WakeupPin::Disabled) || wkt.is_some());
+
+        interrupt::free(|_| {
+            // Clear the flag, so it can reliably be detected after wake-up.
+            self.pmu.pcon.write(|w| {
+                w.pm().deep_power_down_mode();
+                w.dpdflag().deep_power_down()
+            });
+
+            self.pmu.dpdctrl.modify(|_, w| {
+                match wakeup_pin {
+                    WakeupPin::Enabled { hysteresis, .. } => {
+                        w.wakepad_disable().enabled();
+                        w.wakeuphys().bit(hysteresis);
+                    }
+                    WakeupPin::Disabled => {
+                        w.wakepad_disable().disabled();
+                        w.wakeuphys().disabled();
+                    }
+                }
+                w.lposcdpden().bit(wkt.is_some())
+            });
+
+            if let Some((wkt, ticks)) = wkt {
+                wkt.start(ticks);
+            }
+
+            // The SLEEPDEEP bit must be set for entering deep power-down mode.
+            // See user manual, section 6.7.7.2.
             scb.set_sleepdeep();
 
             asm::dsb();
-            asm::wfi();
+            loop {
+                asm::wfi();
+            }
         })
     }
+
+    /// Optimize the internal voltage regulation for the given power profile
+    ///
+    /// Calls the `set_power` function of the power API in the boot ROM. The
+    /// ROM configures the chip for the given profile, at the given system
+    /// clock frequency. See user manual, section 24.4.2.
+    ///
+    /// This method must be called again, whenever the system clock frequency
+    /// changes. `system_clock` is rounded up to full megahertz, and must not be
+    /// higher than 30 MHz.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SetPowerError::InvalidFrequency`], if `system_clock` is zero
+    /// or too high. No call to the ROM is made in that case.
+    ///
+    /// # Example
+    ///
+    /// ``` no_run
+    /// use lpc8xx_hal::{
+    ///     embedded_time::rate::Hertz, pmu::PowerProfile, Peripherals,
+    /// };
+    ///
+    /// let p = Peripherals::take().unwrap();
+    ///
+    /// let mut pmu = p.PMU.split();
+    /// pmu.handle
+    ///     .set_power(PowerProfile::LowCurrent, Hertz(12_000_000))
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "82x")]
+    pub fn set_power(
+        &mut self,
+        profile: PowerProfile,
+        system_clock: Hertz,
+    ) -> Result<(), SetPowerError> {
+        let mhz = ((system_clock.0 as u64 + 999_999) / 1_000_000) as u32;
+        if mhz == 0 || mhz > 30 {
+            return Err(SetPowerError::InvalidFrequency);
+        }
+
+        // The first parameter is the main clock frequency, the third the
+        // system clock frequency. The system clock divider is expected to be
+        // 1, so both are the same.
+        let command = [mhz, profile as u32, mhz];
+        let mut result = [0u32; 2];
+
+        // Sound, as the ROM driver table is located at a fixed address on all
+        // LPC82x parts, and `set_power` only reads three words from the
+        // command and writes up to two words to the result.
+        unsafe {
+            let drivers =
+                *(ROM_DRIVER_TABLE as *const *const *const RomPowerApi);
+            let api = *drivers.add(3);
+            ((*api).set_power)(command.as_ptr(), result.as_mut_ptr());
+        }
+
+        match result[0] {
+            0 => Ok(()),
+            9 => Err(SetPowerError::InvalidFrequency),
+            10 => Err(SetPowerError::InvalidProfile),
+            code => Err(SetPowerError::Unknown(code)),
+        }
+    }
+
+    /// Indicates whether the microcontroller woke up from deep power-down mode
+    ///
+    /// Reads and clears the deep power-down flag. This method should be called
+    /// once after startup, as the flag isn't cleared by any reset other than a
+    /// power-on reset.
+    pub fn woke_from_deep_power_down(&mut self) -> bool {
+        let woke = self.pmu.pcon.read().dpdflag().is_deep_power_down();
+        if woke {
+            // Writing a one clears the flag. All methods that enter a
+            // low-power mode set the power mode themselves, so there's no need
+            // to preserve it.
+            self.pmu.pcon.write(|w| w.dpdflag().deep_power_down());
+        }
+
+        woke
+    }
+
+    /// Read one of the general-purpose registers
+    ///
+    /// The general-purpose registers retain their contents in deep power-down
+    /// mode. Their contents are undefined after a power-on reset.
+    pub fn read_gpreg(&self, reg: Gpreg) -> u32 {
+        self.pmu.gpreg[reg as usize].read().gpdata().bits()
+    }
+
+    /// Write one of the general-purpose registers
+    ///
+    /// See [`Handle::read_gpreg`].
+    pub fn write_gpreg(&mut self, reg: Gpreg, value: u32) {
+        self.pmu.gpreg[reg as usize]
+            .write(|w| unsafe { w.gpdata().bits(value) });
+    }
+
+    /// Store a value in the general-purpose registers
+    ///
+    /// The value is stored in GPREG0-2, together with a checksum in GPREG3. It
+    /// can be loaded again after waking up from deep power-down mode, using
+    /// [`Handle::load_retained`].
+    pub fn store_retained<T>(&mut self, value: &T)
+    where
+        T: Retain,
+    {
+        let registers = encode_retained(value);
+
+        for (reg, &word) in self.pmu.gpreg.iter().zip(registers.iter()) {
+            reg.write(|w| unsafe { w.gpdata().bits(word) });
+        }
+    }
+
+    /// Load a value from the general-purpose registers
+    ///
+    /// Loads a value that was previously stored using
+    /// [`Handle::store_retained`]. Returns `None`, if the checksum doesn't
+    /// match, which is typically the case after a power-on reset.
+    pub fn load_retained<T>(&self) -> Option<T>
+    where
+        T: Retain,
+    {
+        let mut registers = [0; 4];
+        for (word, reg) in registers.iter_mut().zip(self.pmu.gpreg.iter()) {
+            *word = reg.read().gpdata().bits();
+        }
+
+        decode_retained(registers)
+    }
+}
+
+/// Encode a value for the general-purpose registers
+///
+/// Returns the contents of GPREG0-3, as written by [`Handle::store_retained`]:
+/// the three words of the value, followed by their checksum. This function
+/// doesn't access any hardware, so it can be used to test [`Retain`]
+/// implementations on the host.
+///
+/// # Example
+///
+/// ```
+/// use lpc8xx_hal::pmu::{decode_retained, encode_retained};
+///
+/// let registers = encode_retained(&[1, 2, 3]);
+/// assert_eq!(registers[..3], [1, 2, 3]);
+/// assert_eq!(decode_retained::<[u32; 3]>(registers), Some([1, 2, 3]));
+///
+/// // A changed word no longer matches the checksum.
+/// let mut corrupted = registers;
+/// corrupted[1] ^= 0x0100;
+/// assert_eq!(decode_retained::<[u32; 3]>(corrupted), None);
+///
+/// // Neither do cleared registers.
+/// assert_eq!(decode_retained::<[u32; 3]>([0; 4]), None);
+/// ```
+pub fn encode_retained<T>(value: &T) -> [u32; 4]
+where
+    T: Retain,
+{
+    let words = value.to_words();
+    [words[0], words[1], words[2], checksum(&words)]
+}
+
+/// Decode a value from the general-purpose registers
+///
+/// Expects the contents of GPREG0-3, as read by [`Handle::load_retained`].
+/// Returns `None`, if the checksum doesn't match. See [`encode_retained`].
+pub fn decode_retained<T>(registers: [u32; 4]) -> Option<T>
+where
+    T: Retain,
+{
+    let words = [registers[0], registers[1], registers[2]];
+
+    if registers[3] != checksum(&words) {
+        return None;
+    }
+
+    Some(T::from_words(words))
+}
+
+/// One of the general-purpose registers of the PMU
+///
+/// Used by [`Handle::read_gpreg`] and [`Handle::write_gpreg`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Gpreg {
+    /// General-purpose register 0
+    Gpreg0 = 0,
+
+    /// General-purpose register 1
+    Gpreg1 = 1,
+
+    /// General-purpose register 2
+    Gpreg2 = 2,
+
+    /// General-purpose register 3
+    Gpreg3 = 3,
+}
+
+/// Implemented for values that can be retained in the general-purpose registers
+///
+/// Used by [`Handle::store_retained`] and [`Handle::load_retained`]. Three
+/// 32-bit words are available, as the fourth general-purpose register holds a
+/// checksum.
+///
+/// # Example
+///
+/// ``` no_run
+/// use lpc8xx_hal::{pmu::Retain, Peripherals};
+///
+/// struct State {
+///     boot_count: u32,
+///     wake_reason: u8,
+/// }
+///
+/// impl Retain for State {
+///     fn to_words(&self) -> [u32; 3] {
+///         [self.boot_count, self.wake_reason as u32, 0]
+///     }
+///
+///     fn from_words(words: [u32; 3]) -> Self {
+///         Self {
+///             boot_count: words[0],
+///             wake_reason: words[1] as u8,
+///         }
+///     }
+/// }
+///
+/// let p = Peripherals::take().unwrap();
+/// let mut pmu = p.PMU.split();
+///
+/// let mut state = pmu.handle.load_retained().unwrap_or(State {
+///     boot_count: 0,
+///     wake_reason: 0,
+/// });
+/// state.boot_count += 1;
+/// pmu.handle.store_retained(&state);
+/// ```
+pub trait Retain {
+    /// Convert the value into words that can be stored
+    fn to_words(&self) -> [u32; 3];
+
+    /// Restore the value from stored words
+    fn from_words(words: [u32; 3]) -> Self;
+}
+
+impl Retain for [u32; 3] {
+    fn to_words(&self) -> [u32; 3] {
+        *self
+    }
+
+    fn from_words(words: [u32; 3]) -> Self {
+        words
+    }
+}
+
+fn checksum(words: &[u32; 3]) -> u32 {
+    // 32-bit FNV-1a over the bytes of the words. The offset basis makes sure
+    // that all-zero registers are not mistaken for a valid value.
+    words
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .fold(0x811c_9dc5, |hash: u32, byte| {
+            (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+        })
+}
+
+/// A power profile, as understood by the power API in the boot ROM
+///
+/// Used by [`Handle::set_power`].
+#[cfg(feature = "82x")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PowerProfile {
+    /// The default configuration after reset
+    Default = 0,
+
+    /// Optimize for CPU performance
+    CpuPerformance = 1,
+
+    /// Optimize for efficiency, balancing performance and current
+    Efficiency = 2,
+
+    /// Optimize for low active current
+    LowCurrent = 3,
+}
+
+/// Returned by [`Handle::set_power`], if the ROM reported an error
+#[cfg(feature = "82x")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SetPowerError {
+    /// The system clock frequency is not supported
+    InvalidFrequency,
+
+    /// The ROM didn't accept the power profile
+    InvalidProfile,
+
+    /// The ROM returned an unknown status code
+    Unknown(u32),
+}
+
+/// Address of the pointer to the ROM driver table
+///
+/// See user manual, section 24.4.
+#[cfg(feature = "82x")]
+const ROM_DRIVER_TABLE: usize = 0x1fff_1ff8;
+
+/// The power API in the boot ROM
+///
+/// A pointer to this table is located at offset 0x0C of the ROM driver table.
+/// See user manual, section 24.4.1.
+#[cfg(feature = "82x")]
+#[repr(C)]
+struct RomPowerApi {
+    _set_pll: unsafe extern "C" fn(*const u32, *mut u32),
+    set_power: unsafe extern "C" fn(*const u32, *mut u32),
+}
+
+/// The SEVONPEND bit in the SCR register
+const SCB_SCR_SEVONPEND: u32 = 0x1 << 4;
+
+/// A power mode that is entered by sleeping
+///
+/// Used by [`Handle::enable_sleep_on_exit`], [`Handle::wait_for_interrupt`],
+/// and [`Handle::wait_for_event`]. Deep power-down mode is not included, as
+/// waking up from it causes a reset. Use [`Handle::enter_deep_power_down`] for
+/// that.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PowerMode(Pm);
+
+impl PowerMode {
+    /// Regular sleep mode
+    ///
+    /// See user manual, section 6.7.4.
+    pub fn sleep() -> Self {
+        Self(Pm::Sleep)
+    }
+
+    /// Deep-sleep mode
+    ///
+    /// See user manual, section 6.7.5.
+    ///
+    /// # Safety
+    ///
+    /// The same requirements as for [`Handle::enter_deep_sleep_mode`] apply,
+    /// whenever this power mode is entered.
+    pub unsafe fn deep_sleep() -> Self {
+        Self(Pm::DeepSleep)
+    }
+
+    /// Power-down mode
+    ///
+    /// See user manual, section 6.7.6.
+    ///
+    /// # Safety
+    ///
+    /// The same requirements as for [`Handle::enter_power_down_mode`] apply,
+    /// whenever this power mode is entered.
+    pub unsafe fn power_down() -> Self {
+        Self(Pm::PowerDown)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Pm {
+    Sleep,
+    DeepSleep,
+    PowerDown,
+}
+
+/// Configuration of the WAKEUP pin for deep power-down mode
+///
+/// Used by [`Handle::enter_deep_power_down`]. The WAKEUP pin is PIO0_4 on all
+/// supported targets. A falling edge on this pin wakes the microcontroller up
+/// from deep power-down mode.
+pub enum WakeupPin {
+    /// The WAKEUP pin can wake up the microcontroller
+    ///
+    /// Consumes the unused pin PIO0_4, so it can't be reconfigured while it's
+    /// armed as a wake-up source.
+    Enabled {
+        /// The WAKEUP pin
+        pin: Pin<PIO0_4, pins::state::Unused>,
+
+        /// Whether the hysteresis of the WAKEUP pin should be enabled
+        hysteresis: bool,
+    },
+
+    /// The WAKEUP pin is disabled
+    ///
+    /// Only allowed, if the WKT is used to wake up the microcontroller.
+    Disabled,
 }
 
 /// The 10 kHz low-power clock
 ///
 /// This is one of the clocks that can be used to run the self-wake-up timer
 /// (WKT). See user manual, section 18.5.1.
+///
+/// The low-power oscillator is only accurate to ±40% of its nominal frequency.
+/// If more accuracy is required, its actual frequency can be measured and
+/// declared using [`LowPowerClock::calibrate`].
 pub struct LowPowerClock<State = init_state::Enabled> {
     _state: State,
+    hz: u32,
 }
 
 impl LowPowerClock<init_state::Disabled> {
     pub(crate) fn new() -> Self {
         LowPowerClock {
             _state: init_state::Disabled,
+            hz: 10_000,
         }
     }
 }
 
+impl<State> LowPowerClock<State> {
+    /// Declare the measured frequency of the low-power clock
+    ///
+    /// By default, the nominal frequency of 10 kHz is assumed. The frequency
+    /// can be measured, for example by timing a number of WKT ticks using the
+    /// MRT, and declared here. It is then used by everything that converts
+    /// between WKT ticks and time, like the delays of the WKT, and
+    /// [`idle::Idle`].
+    ///
+    /// The clock can't be calibrated while it's borrowed by the WKT.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `hz` is zero.
+    ///
+    /// [`idle::Idle`]: crate::idle::Idle
+    pub fn calibrate(&mut self, hz: u32) {
+        assert!(hz != 0);
+        self.hz = hz;
+    }
+}
+
 impl LowPowerClock<init_state::Disabled> {
     /// Enable the low-power clock
     ///
@@ -250,6 +885,7 @@ impl LowPowerClock<init_state::Disabled> {
 
         LowPowerClock {
             _state: init_state::Enabled(()),
+            hz: self.hz,
         }
     }
 }
@@ -274,14 +910,92 @@ impl LowPowerClock<init_state::Enabled> {
 
         LowPowerClock {
             _state: init_state::Disabled,
+            hz: self.hz,
         }
     }
 }
 
 impl<State> clock::Frequency for LowPowerClock<State> {
     fn hz(&self) -> u32 {
-        10_000
+        self.hz
     }
 }
 
 impl clock::Enabled for LowPowerClock<init_state::Enabled> {}
+
+/// An external clock, supplied via the WKTCLKIN pin
+///
+/// This is one of the clocks that can be used to run the self-wake-up timer
+/// (WKT). The clock signal is supplied to the WKTCLKIN function of pin PIO0_28.
+/// See user manual, chapter 9.
+///
+/// As the frequency of the external clock can't be determined by the
+/// microcontroller, it has to be declared by the user when creating this
+/// clock. A 32.768 kHz watch crystal oscillator is a typical source.
+///
+/// # Example
+///
+/// ``` no_run
+/// use lpc8xx_hal::{pmu::WktClkIn, Peripherals};
+///
+/// let p = Peripherals::take().unwrap();
+///
+/// let mut pmu = p.PMU.split();
+/// let mut syscon = p.SYSCON.split();
+/// let clock = WktClkIn::new(p.pins.pio0_28, 32_768, &mut pmu.handle);
+/// let wkt = p.WKT.enable(&mut syscon.handle).select_clock(&clock);
+/// ```
+pub struct WktClkIn {
+    pin: Pin<PIO0_28, pins::state::Unused>,
+    hz: u32,
+}
+
+impl WktClkIn {
+    /// Enable the WKTCLKIN pin and declare the frequency of its clock
+    ///
+    /// Consumes the unused pin PIO0_28, which is switched to its WKTCLKIN
+    /// function, with hysteresis enabled. The frequency `hz` must match the
+    /// frequency of the clock signal supplied to the pin. It is not verified
+    /// in any way, but used by APIs that need to know the clock frequency.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `hz` is zero.
+    pub fn new(
+        pin: Pin<PIO0_28, pins::state::Unused>,
+        hz: u32,
+        pmu: &mut Handle,
+    ) -> Self {
+        assert!(hz != 0);
+
+        pmu.pmu.dpdctrl.modify(|_, w| {
+            w.wakeupclkhys().enabled();
+            w.wakeclkpad_disable().enabled()
+        });
+
+        Self { pin, hz }
+    }
+
+    /// Disable the WKTCLKIN pin
+    ///
+    /// Returns the pin, which can then be used for other purposes.
+    pub fn disable(
+        self,
+        pmu: &mut Handle,
+    ) -> Pin<PIO0_28, pins::state::Unused> {
+        pmu.pmu.dpdctrl.modify(|_, w| {
+            w.wakeclkpad_disable().disabled();
+            w.wakeupclkhys().disabled()
+        });
+
+        self.pin
+    }
+}
+
+impl clock::Frequency for WktClkIn {
+    fn hz(&self) -> u32 {
+        self.hz
+    }
+}
+
+impl clock::Enabled for WktClkIn {}
@@ -0,0 +1,241 @@
+//! Contains types related to SCT PWM channels
+
+use core::{convert::Infallible, marker::PhantomData};
+
+use embedded_hal::PwmPin;
+use embedded_hal_alpha::pwm::PwmPin as PwmPinAlpha;
+
+use crate::{pac, swm};
+
+use super::{
+    half::{self, MATCHREL},
+    timer::limit_event,
+};
+
+use self::state::{Attached, Detached};
+
+/// A PWM channel that belongs to one half of the SCT
+///
+/// Each channel controls one of the SCT outputs. The output is set at the
+/// beginning of each period of the counter half the channel belongs to, and
+/// cleared once the configured duty cycle has passed.
+///
+/// The period is set using [`Timer::start`]. The duty cycle is measured in
+/// ticks of the timer, and can range from 0 (output always low) to the value
+/// returned by `get_max_duty`, which is equal to the period (output always
+/// high). Changes to the duty cycle take effect at the end of the current
+/// period.
+///
+/// # `embedded-hal` traits
+/// - [`embedded_hal::PwmPin`]
+///
+/// [`Timer::start`]: super::Timer::start
+/// [`embedded_hal::PwmPin`]: #impl-PwmPin
+pub struct Channel<H, C, State> {
+    _half: PhantomData<H>,
+    _channel: PhantomData<C>,
+    _state: PhantomData<State>,
+}
+
+impl<H, C, State> Channel<H, C, State> {
+    pub(super) fn new() -> Self {
+        Self {
+            _half: PhantomData,
+            _channel: PhantomData,
+            _state: PhantomData,
+        }
+    }
+}
+
+impl<H, C> Channel<H, C, Detached>
+where
+    H: half::Trait,
+    C: Trait<H>,
+{
+    /// Attach an output function to this channel
+    ///
+    /// This method is only available, if no output function has been attached
+    /// to the channel yet.
+    pub fn attach<Pin>(
+        self,
+        _: swm::Function<C::Output, swm::state::Assigned<Pin>>,
+    ) -> Channel<H, C, Attached> {
+        Channel::new()
+    }
+}
+
+impl<H, C> Channel<H, C, Attached>
+where
+    H: half::Trait,
+    C: Trait<H>,
+{
+    /// Detach the output function from this channel
+    ///
+    /// Sets the duty cycle to zero, which means the output is low from the end
+    /// of the current period on. The output function stays assigned to its
+    /// pin.
+    pub fn detach(mut self) -> Channel<H, C, Detached> {
+        self.set_duty(0);
+        Channel::new()
+    }
+
+    fn get_duty(&self) -> u16 {
+        if sct().out[C::OUTPUT].set.read().set().bits() == 0 {
+            return 0;
+        }
+
+        half::read::<H>(MATCHREL + match_offset::<H, C>()) + 1
+    }
+
+    fn get_max_duty(&self) -> u16 {
+        half::read::<H>(MATCHREL) + 1
+    }
+
+    fn set_duty(&mut self, duty: u16) {
+        let duty = duty.min(self.get_max_duty());
+
+        // The output is set by the limit event, and cleared by this channel's
+        // event. If the duty cycle is zero, we need to prevent the output from
+        // being set at all.
+        let set = if duty == 0 {
+            0
+        } else {
+            half::write::<H>(MATCHREL + match_offset::<H, C>(), duty - 1);
+            limit_event::<H>()
+        };
+
+        sct().out[C::OUTPUT]
+            .set
+            .write(|w| unsafe { w.set().bits(set as _) });
+    }
+}
+
+impl<H, C> PwmPin for Channel<H, C, Attached>
+where
+    H: half::Trait,
+    C: Trait<H>,
+{
+    type Duty = u16;
+
+    /// The behaviour of `enable` is implementation defined and does nothing in
+    /// this implementation
+    fn enable(&mut self) {}
+
+    /// The behaviour of `disable` is implementation defined and does nothing in
+    /// this implementation
+    fn disable(&mut self) {}
+
+    /// Returns the current duty cycle
+    fn get_duty(&self) -> Self::Duty {
+        self.get_duty()
+    }
+
+    /// Returns the maximum duty cycle value
+    fn get_max_duty(&self) -> Self::Duty {
+        self.get_max_duty()
+    }
+
+    /// Sets a new duty cycle
+    fn set_duty(&mut self, duty: Self::Duty) {
+        self.set_duty(duty)
+    }
+}
+
+impl<H, C> PwmPinAlpha for Channel<H, C, Attached>
+where
+    H: half::Trait,
+    C: Trait<H>,
+{
+    type Error = Infallible;
+    type Duty = u16;
+
+    /// The behaviour of `enable` is implementation defined and does nothing in
+    /// this implementation
+    fn try_enable(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// The behaviour of `disable` is implementation defined and does nothing in
+    /// this implementation
+    fn try_disable(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Returns the current duty cycle
+    fn try_get_duty(&self) -> Result<Self::Duty, Self::Error> {
+        Ok(self.get_duty())
+    }
+
+    /// Returns the maximum duty cycle value
+    fn try_get_max_duty(&self) -> Result<Self::Duty, Self::Error> {
+        Ok(self.get_max_duty())
+    }
+
+    /// Sets a new duty cycle
+    fn try_set_duty(&mut self, duty: Self::Duty) -> Result<(), Self::Error> {
+        self.set_duty(duty);
+        Ok(())
+    }
+}
+
+/// Implemented for all PWM channels of an SCT counter half
+pub trait Trait<H>: private::Sealed {
+    /// Identifies the channel within its counter half
+    const ID: u8;
+
+    /// The index of the SCT output that is controlled by this channel
+    const OUTPUT: usize;
+
+    /// The SWM function that needs to be assigned to this channel's output pin
+    type Output;
+}
+
+/// Contains types that indicate which state a channel is in
+pub mod state {
+    /// Indicates that a channel is detached
+    ///
+    /// Detached channels don't have an output function assigned and can't be
+    /// used for PWM output.
+    pub struct Detached;
+
+    /// Indicates that a channel is attached
+    pub struct Attached;
+}
+
+/// Configures the output that is controlled by a channel
+///
+/// Must be called once for each channel, when the SCT is split.
+pub(super) fn configure<H, C>(sct: &pac::SCT0)
+where
+    H: half::Trait,
+    C: Trait<H>,
+{
+    let event = H::EVENT_BASE + 1 + C::ID as usize;
+
+    // The duty cycle is initially zero, so the output is never set.
+    sct.out[C::OUTPUT].set.write(|w| unsafe { w.set().bits(0) });
+    sct.out[C::OUTPUT]
+        .clr
+        .write(|w| unsafe { w.clr().bits(0x1 << event) });
+}
+
+fn match_offset<H, C>() -> usize
+where
+    H: half::Trait,
+    C: Trait<H>,
+{
+    // Match register 0 holds the limit. The channels use the following ones.
+    // Each match register is 4 bytes wide.
+    (1 + C::ID as usize) * 4
+}
+
+fn sct() -> &'static pac::sct0::RegisterBlock {
+    // Sound, as every channel only accesses the OUT registers of its own
+    // output, which no other part of the API touches after the SCT has been
+    // split.
+    unsafe { &*pac::SCT0::ptr() }
+}
+
+pub(super) mod private {
+    pub trait Sealed {}
+}
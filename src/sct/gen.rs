@@ -0,0 +1,79 @@
+use crate::swm;
+
+use super::{
+    channel::{self, state::Detached, Channel},
+    half::{H, L},
+};
+
+/// Contains the PWM channels of one SCT counter half
+///
+/// Can be accessed via [`Halves`].
+///
+/// [`Halves`]: super::Halves
+#[allow(missing_docs)]
+pub struct Channels<
+    Half,
+    State1 = Detached,
+    State2 = Detached,
+    State3 = Detached,
+> {
+    pub channel1: Channel<Half, Channel1, State1>,
+    pub channel2: Channel<Half, Channel2, State2>,
+    pub channel3: Channel<Half, Channel3, State3>,
+}
+
+impl<Half> Channels<Half> {
+    pub(super) fn new() -> Self {
+        Self {
+            channel1: Channel::new(),
+            channel2: Channel::new(),
+            channel3: Channel::new(),
+        }
+    }
+}
+
+macro_rules! channels {
+    (
+        $(
+            $channel:ident,
+            $id:expr,
+            $output_l:expr,
+            $function_l:ident,
+            $output_h:expr,
+            $function_h:ident;
+        )*
+    ) => {
+        $(
+            /// Identifies an SCT PWM channel
+            pub struct $channel;
+
+            impl channel::private::Sealed for $channel {}
+
+            impl channel::Trait<L> for $channel {
+                const ID: u8 = $id;
+                const OUTPUT: usize = $output_l;
+                type Output = swm::$function_l;
+            }
+
+            impl channel::Trait<H> for $channel {
+                const ID: u8 = $id;
+                const OUTPUT: usize = $output_h;
+                type Output = swm::$function_h;
+            }
+        )*
+
+        /// Configures the outputs of all channels
+        pub(super) fn configure_channels(sct: &crate::pac::SCT0) {
+            $(
+                channel::configure::<L, $channel>(sct);
+                channel::configure::<H, $channel>(sct);
+            )*
+        }
+    };
+}
+
+channels! {
+    Channel1, 0, 0, SCT_OUT0, 3, SCT_OUT3;
+    Channel2, 1, 1, SCT_OUT1, 4, SCT_OUT4;
+    Channel3, 2, 2, SCT_OUT2, 5, SCT_OUT5;
+}
@@ -0,0 +1,85 @@
+//! Contains types that identify the halves of the SCT counter
+
+use core::ptr;
+
+use crate::pac;
+
+/// Implemented for types that identify one half of the SCT counter
+///
+/// If the SCT operates as two 16-bit counters, all counter-related registers
+/// are split into an L and an H half, each of which can be accessed
+/// separately. This trait provides the information that is required to do
+/// that.
+pub trait Trait: private::Sealed {
+    /// Byte offset of this half within a split register
+    const OFFSET: usize;
+
+    /// Whether events that belong to this half use the H counter
+    const HEVENT: bool;
+
+    /// The first of the events that are reserved for this half
+    ///
+    /// The event with this number is used as the limit event. The following
+    /// events are used by the PWM channels of this half.
+    const EVENT_BASE: usize;
+}
+
+/// Identifies the lower half of the SCT counter (counter L)
+pub struct L;
+
+impl private::Sealed for L {}
+
+impl Trait for L {
+    const OFFSET: usize = 0;
+    const HEVENT: bool = false;
+    const EVENT_BASE: usize = 0;
+}
+
+/// Identifies the upper half of the SCT counter (counter H)
+pub struct H;
+
+impl private::Sealed for H {}
+
+impl Trait for H {
+    const OFFSET: usize = 2;
+    const HEVENT: bool = true;
+    const EVENT_BASE: usize = 4;
+}
+
+// Offsets of split registers within the SCT register block
+pub(super) const CTRL: usize = 0x004;
+pub(super) const COUNT: usize = 0x040;
+pub(super) const MATCH: usize = 0x100;
+pub(super) const MATCHREL: usize = 0x200;
+
+// Fields of the CTRL_L/CTRL_H registers
+pub(super) const CTRL_HALT: u16 = 0x1 << 2;
+pub(super) const CTRL_CLRCTR: u16 = 0x1 << 3;
+pub(super) const CTRL_PRE_SHIFT: u16 = 5;
+pub(super) const CTRL_PRE_MASK: u16 = 0xff << CTRL_PRE_SHIFT;
+
+/// Reads the half of a split register that belongs to `H`
+///
+/// `offset` must be the offset of a split register, as defined above.
+pub(super) fn read<H: Trait>(offset: usize) -> u16 {
+    // Sound, as the address is valid, properly aligned, and the SCT allows
+    // 16-bit access to the halves of its split registers.
+    unsafe { ptr::read_volatile(address::<H>(offset)) }
+}
+
+/// Writes the half of a split register that belongs to `H`
+///
+/// `offset` must be the offset of a split register, as defined above.
+pub(super) fn write<H: Trait>(offset: usize, value: u16) {
+    // Sound, for the same reasons given in `read`. Only the half that belongs
+    // to `H` is written, so this can't interfere with the other half.
+    unsafe { ptr::write_volatile(address::<H>(offset), value) }
+}
+
+fn address<H: Trait>(offset: usize) -> *mut u16 {
+    (pac::SCT0::ptr() as usize + offset + H::OFFSET) as *mut u16
+}
+
+mod private {
+    pub trait Sealed {}
+}
@@ -0,0 +1,46 @@
+//! Contains types related to SCT inputs
+
+use crate::{pac, swm};
+
+/// Implemented for SWM functions that can be used as SCT inputs
+///
+/// Each of the `SCT_PINn` functions is routed to SCT input `n` through the
+/// input multiplexer.
+pub trait Trait: private::Sealed {
+    /// The number of the SCT input that this function is routed to
+    const ID: u8;
+}
+
+macro_rules! inputs {
+    ($($function:ident, $id:expr;)*) => {
+        $(
+            impl private::Sealed for swm::$function {}
+
+            impl Trait for swm::$function {
+                const ID: u8 = $id;
+            }
+        )*
+    };
+}
+
+inputs! {
+    SCT_PIN0, 0;
+    SCT_PIN1, 1;
+    SCT_PIN2, 2;
+    SCT_PIN3, 3;
+}
+
+/// Routes the `SCT_PINn` function of `I` to SCT input `n`
+pub(super) fn select<I: Trait>(inputmux: &pac::INPUTMUX) {
+    #[cfg(feature = "82x")]
+    let inmux = &inputmux.sct0_inmux[I::ID as usize];
+    #[cfg(feature = "845")]
+    let inmux = &inputmux.sct_inmux[I::ID as usize];
+
+    // Sound, as values 0 to 3 select `SCT_PIN0` to `SCT_PIN3`.
+    inmux.write(|w| unsafe { w.inp_n().bits(I::ID) });
+}
+
+mod private {
+    pub trait Sealed {}
+}
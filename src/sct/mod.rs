@@ -0,0 +1,69 @@
+//! API for the State Configurable Timer (SCT)
+//!
+//! The entry point to this API is [`SCT`].
+//!
+//! The SCT can be split into two independent 16-bit timers, each of which can
+//! be used as a periodic timer and provides three PWM channels. Alternatively,
+//! it can generate complementary PWM signals for motor control (see [`motor`]),
+//! or decode the signals of a quadrature encoder (see [`quadrature`]).
+//!
+//! # Example
+//!
+//! Generate a PWM signal using counter L, while counter H provides a periodic
+//! tick:
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{prelude::*, Peripherals};
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let mut syscon = p.SYSCON.split();
+//! let mut swm = p.SWM.split();
+//!
+//! #[cfg(feature = "82x")]
+//! let mut swm_handle = swm.handle;
+//! #[cfg(feature = "845")]
+//! let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+//!
+//! let (pwm_output, _) = swm.movable_functions.sct_out0.assign(
+//!     p.pins.pio0_17.into_swm_pin(),
+//!     &mut swm_handle,
+//! );
+//!
+//! let sct = p.SCT0.enable(&mut syscon.handle).split();
+//!
+//! // 1 kHz PWM carrier, assuming a system clock of 12 MHz
+//! let mut carrier = sct.timer_l;
+//! carrier.start(12_000u16);
+//!
+//! let mut pwm = sct.channels_l.channel1.attach(pwm_output);
+//! pwm.set_duty(pwm.get_max_duty() / 2);
+//!
+//! // 100 Hz tick
+//! let mut tick = sct.timer_h;
+//! tick.set_prescaler(9);
+//! tick.start(12_000u16);
+//!
+//! loop {
+//!     nb::block!(tick.wait()).unwrap();
+//!     // do something every 10 ms
+//! }
+//! ```
+
+pub mod channel;
+pub mod half;
+pub mod input;
+pub mod motor;
+pub mod quadrature;
+
+mod gen;
+mod peripheral;
+mod timer;
+
+pub use self::{
+    channel::Channel,
+    gen::{Channel1, Channel2, Channel3, Channels},
+    half::{H, L},
+    peripheral::{Halves, SCT},
+    timer::Timer,
+};
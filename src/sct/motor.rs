@@ -0,0 +1,592 @@
+//! Motor-control PWM with complementary outputs
+//!
+//! In this mode, the SCT operates as a single 32-bit counter, which counts up
+//! to its limit and back down to zero, producing center-aligned PWM signals.
+//! Each [`Pair`] controls two outputs: a high-side output and a complementary
+//! low-side output, as required to drive a half-bridge. A configurable dead
+//! time is inserted between switching off one output and switching on the
+//! other, to prevent shoot-through.
+//!
+//! An optional fault input ([`Timer::enable_fault_input`]) halts the counter
+//! and switches off all outputs in hardware, as soon as it becomes active.
+//!
+//! Use [`SCT::motor_control`] to enter this mode.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{prelude::*, sct::motor::FaultPolarity, Peripherals};
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let mut syscon = p.SYSCON.split();
+//! let mut swm = p.SWM.split();
+//!
+//! #[cfg(feature = "82x")]
+//! let mut swm_handle = swm.handle;
+//! #[cfg(feature = "845")]
+//! let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+//!
+//! let (high, _) = swm.movable_functions.sct_out0.assign(
+//!     p.pins.pio0_16.into_swm_pin(),
+//!     &mut swm_handle,
+//! );
+//! let (low, _) = swm.movable_functions.sct_out1.assign(
+//!     p.pins.pio0_17.into_swm_pin(),
+//!     &mut swm_handle,
+//! );
+//! let (fault, _) = swm.movable_functions.sct_pin0.assign(
+//!     p.pins.pio0_18.into_swm_pin(),
+//!     &mut swm_handle,
+//! );
+//!
+//! // 20 kHz PWM with a dead time of 0.5 µs, assuming a system clock of 12 MHz
+//! let mut motor = p.SCT0.enable(&mut syscon.handle).motor_control(600, 6);
+//!
+//! motor.timer.enable_fault_input(
+//!     fault,
+//!     FaultPolarity::ActiveLow,
+//!     &p.INPUTMUX,
+//! );
+//!
+//! let mut phase = motor.pair1.attach(high, low);
+//! phase.set_duty(phase.get_max_duty() / 4);
+//!
+//! motor.timer.start();
+//! ```
+//!
+//! [`SCT::motor_control`]: super::SCT::motor_control
+
+use core::{convert::Infallible, marker::PhantomData, ptr};
+
+use embedded_hal::PwmPin;
+use embedded_hal_alpha::pwm::PwmPin as PwmPinAlpha;
+
+use crate::{init_state::Enabled, pac, swm, syscon};
+
+use super::{
+    channel::state::{Attached, Detached},
+    half::{MATCH, MATCHREL},
+    input, SCT,
+};
+
+/// The SCT in motor-control mode
+///
+/// Returned by [`SCT::motor_control`]. Use [`MotorControl::reset`] to get the
+/// [`SCT`] back.
+///
+/// [`SCT::motor_control`]: super::SCT::motor_control
+/// [`SCT`]: super::SCT
+pub struct MotorControl {
+    sct: pac::SCT0,
+
+    /// The timer that drives all output pairs
+    pub timer: Timer,
+
+    /// Output pair 1 (`SCT_OUT0` and `SCT_OUT1`)
+    pub pair1: Pair<Pair1, Detached>,
+
+    /// Output pair 2 (`SCT_OUT2` and `SCT_OUT3`)
+    pub pair2: Pair<Pair2, Detached>,
+
+    /// Output pair 3 (`SCT_OUT4` and `SCT_OUT5`)
+    pub pair3: Pair<Pair3, Detached>,
+}
+
+impl MotorControl {
+    pub(super) fn new(sct: pac::SCT0, dead_time: u32) -> Self {
+        Self {
+            sct,
+            timer: Timer { _private: () },
+            pair1: Pair::new(dead_time),
+            pair2: Pair::new(dead_time),
+            pair3: Pair::new(dead_time),
+        }
+    }
+
+    /// Reset the SCT and leave motor-control mode
+    ///
+    /// Consumes the timer and all output pairs. Pairs that have been attached
+    /// need to be detached using [`Pair::detach`] first. Resets the SCT, which
+    /// stops the timer and switches off all outputs.
+    pub fn reset(self, syscon: &mut syscon::Handle) -> SCT<Enabled> {
+        SCT::reset(self.sct, syscon)
+    }
+}
+
+/// The timer that drives all output pairs in motor-control mode
+///
+/// The timer is clocked by the system clock, divided by the prescaler (see
+/// [`Timer::set_prescaler`]). It is halted initially and needs to be started
+/// using [`Timer::start`].
+pub struct Timer {
+    _private: (),
+}
+
+impl Timer {
+    /// Set the prescaler of the timer
+    ///
+    /// The timer will be clocked by the system clock, divided by
+    /// `prescaler + 1`. Should only be called while the timer is stopped.
+    pub fn set_prescaler(&mut self, prescaler: u8) {
+        sct()
+            .ctrl
+            .modify(|_, w| unsafe { w.pre_l().bits(prescaler) });
+    }
+
+    /// Set the PWM period
+    ///
+    /// `period` is the length of a full PWM period in timer ticks. Since the
+    /// counter counts up and down during each period, odd values are rounded
+    /// down. Takes effect at the start of the next period.
+    ///
+    /// The duty cycles of all pairs are measured relative to the period, so
+    /// they need to be set again after calling this method.
+    pub fn set_period(&mut self, period: u32) {
+        write_reg(MATCHREL, limit(period));
+    }
+
+    /// Start the timer
+    ///
+    /// Also resumes the timer after it has been halted by the fault input, and
+    /// resets the flag returned by [`Timer::fault_occurred`]. If the fault
+    /// input is still active, the timer is halted again immediately.
+    pub fn start(&mut self) {
+        sct()
+            .evflag
+            .write(|w| unsafe { w.flag().bits(FAULT_EVENTS) });
+        sct().ctrl.modify(|_, w| w.halt_l().clear_bit());
+    }
+
+    /// Stop the timer
+    ///
+    /// The outputs keep their current state.
+    pub fn stop(&mut self) {
+        sct().ctrl.modify(|_, w| w.halt_l().set_bit());
+    }
+
+    /// Indicates whether the timer is running
+    pub fn is_running(&self) -> bool {
+        sct().ctrl.read().halt_l().bit_is_clear()
+    }
+
+    /// Enable the fault input
+    ///
+    /// Routes the given `SCT_PINn` function to SCT input `n`. As soon as this
+    /// input becomes active, as defined by `polarity`, all outputs are switched
+    /// off and the timer is halted, without any involvement of software.
+    ///
+    /// Use [`Timer::fault_occurred`] to check whether this has happened, and
+    /// [`Timer::start`] to resume operation.
+    pub fn enable_fault_input<I, Pin>(
+        &mut self,
+        _: swm::Function<I, swm::state::Assigned<Pin>>,
+        polarity: FaultPolarity,
+        inputmux: &pac::INPUTMUX,
+    ) where
+        I: input::Trait,
+    {
+        input::select::<I>(inputmux);
+
+        for &event in &[FAULT_UP, FAULT_DOWN] {
+            let event = &sct().event[event];
+
+            event.ctrl.modify(|_, w| {
+                unsafe { w.iosel().bits(I::ID) };
+                match polarity {
+                    FaultPolarity::ActiveLow => w.iocond().low(),
+                    FaultPolarity::ActiveHigh => w.iocond().high(),
+                }
+            });
+            event.state.write(|w| unsafe { w.statemskn().bits(0x1) });
+        }
+    }
+
+    /// Indicates whether the fault input has been activated
+    ///
+    /// Stays `true` until the timer is started again.
+    pub fn fault_occurred(&self) -> bool {
+        sct().evflag.read().flag().bits() & FAULT_EVENTS != 0
+    }
+}
+
+/// The active level of the fault input
+///
+/// Used by [`Timer::enable_fault_input`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FaultPolarity {
+    /// A fault is signaled by a low level
+    ActiveLow,
+
+    /// A fault is signaled by a high level
+    ActiveHigh,
+}
+
+/// A pair of complementary PWM outputs
+///
+/// The high-side output is switched on in the middle of each PWM period, for
+/// the configured duty cycle. The low-side output is switched on during the
+/// rest of the period, except for the dead time before and after each pulse of
+/// the high-side output, during which both outputs are off.
+///
+/// The duty cycle is relative to the value returned by `get_max_duty`, which
+/// corresponds to half the PWM period, in timer ticks. Values that leave no
+/// room for the dead time are reduced accordingly. Changes take effect at the
+/// start of the next period.
+///
+/// Both outputs stay off until a duty cycle other than zero is set for the
+/// first time. Afterwards, a duty cycle of zero leaves the low-side output
+/// switched on permanently.
+///
+/// # `embedded-hal` traits
+/// - [`embedded_hal::PwmPin`]
+///
+/// [`embedded_hal::PwmPin`]: #impl-PwmPin
+pub struct Pair<P, State> {
+    dead_time: u32,
+    _pair: PhantomData<P>,
+    _state: PhantomData<State>,
+}
+
+impl<P, State> Pair<P, State> {
+    fn new(dead_time: u32) -> Self {
+        Self {
+            dead_time,
+            _pair: PhantomData,
+            _state: PhantomData,
+        }
+    }
+}
+
+impl<P> Pair<P, Detached>
+where
+    P: Trait,
+{
+    /// Attach the output functions to this pair
+    ///
+    /// This method is only available, if no output functions have been
+    /// attached to the pair yet.
+    pub fn attach<High, Low>(
+        self,
+        _high: swm::Function<P::High, swm::state::Assigned<High>>,
+        _low: swm::Function<P::Low, swm::state::Assigned<Low>>,
+    ) -> Pair<P, Attached> {
+        Pair::new(self.dead_time)
+    }
+}
+
+impl<P> Pair<P, Attached>
+where
+    P: Trait,
+{
+    /// Detach the output functions from this pair
+    ///
+    /// Stops switching the outputs. They keep their current state, until the
+    /// SCT is reset using [`MotorControl::reset`], which switches them off.
+    /// The output functions stay assigned to their pins.
+    pub fn detach(self) -> Pair<P, Detached> {
+        write_reg(MATCHREL + 4 * high_match::<P>(), NEVER);
+        write_reg(MATCHREL + 4 * low_match::<P>(), NEVER);
+
+        Pair::new(self.dead_time)
+    }
+
+    fn get_duty(&self) -> u32 {
+        let high = read_reg(MATCHREL + 4 * high_match::<P>());
+        if high == NEVER {
+            return 0;
+        }
+
+        // The period might have been reduced below the high match, since the
+        // duty cycle was last set.
+        read_reg(MATCHREL).saturating_sub(high)
+    }
+
+    fn get_max_duty(&self) -> u32 {
+        read_reg(MATCHREL)
+    }
+
+    fn set_duty(&mut self, duty: u32) {
+        let limit = read_reg(MATCHREL);
+        let duty =
+            duty.min(limit.saturating_sub(self.dead_time.saturating_add(1)));
+
+        let (high, low) = if duty == 0 {
+            (NEVER, NEVER)
+        } else {
+            let high = limit - duty;
+            (high, high - self.dead_time)
+        };
+
+        // The reload registers might be transferred to the match registers
+        // between the two writes. Write them in an order that makes sure the
+        // dead time is never violated, not even for a single period.
+        let high_reg = MATCHREL + 4 * high_match::<P>();
+        let low_reg = MATCHREL + 4 * low_match::<P>();
+        if high < read_reg(high_reg) {
+            write_reg(low_reg, low);
+            write_reg(high_reg, high);
+        } else {
+            write_reg(high_reg, high);
+            write_reg(low_reg, low);
+        }
+    }
+}
+
+impl<P> PwmPin for Pair<P, Attached>
+where
+    P: Trait,
+{
+    type Duty = u32;
+
+    /// The behaviour of `enable` is implementation defined and does nothing in
+    /// this implementation
+    fn enable(&mut self) {}
+
+    /// The behaviour of `disable` is implementation defined and does nothing in
+    /// this implementation
+    fn disable(&mut self) {}
+
+    /// Returns the current duty cycle
+    fn get_duty(&self) -> Self::Duty {
+        self.get_duty()
+    }
+
+    /// Returns the maximum duty cycle value
+    fn get_max_duty(&self) -> Self::Duty {
+        self.get_max_duty()
+    }
+
+    /// Sets a new duty cycle
+    fn set_duty(&mut self, duty: Self::Duty) {
+        self.set_duty(duty)
+    }
+}
+
+impl<P> PwmPinAlpha for Pair<P, Attached>
+where
+    P: Trait,
+{
+    type Error = Infallible;
+    type Duty = u32;
+
+    /// The behaviour of `enable` is implementation defined and does nothing in
+    /// this implementation
+    fn try_enable(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// The behaviour of `disable` is implementation defined and does nothing in
+    /// this implementation
+    fn try_disable(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Returns the current duty cycle
+    fn try_get_duty(&self) -> Result<Self::Duty, Self::Error> {
+        Ok(self.get_duty())
+    }
+
+    /// Returns the maximum duty cycle value
+    fn try_get_max_duty(&self) -> Result<Self::Duty, Self::Error> {
+        Ok(self.get_max_duty())
+    }
+
+    /// Sets a new duty cycle
+    fn try_set_duty(&mut self, duty: Self::Duty) -> Result<(), Self::Error> {
+        self.set_duty(duty);
+        Ok(())
+    }
+}
+
+/// Implemented for all output pairs
+pub trait Trait: private::Sealed {
+    /// Identifies the pair
+    const ID: usize;
+
+    /// The SWM function of the high-side output
+    type High;
+
+    /// The SWM function of the low-side output
+    type Low;
+}
+
+macro_rules! pairs {
+    ($($pair:ident, $id:expr, $high:ident, $low:ident;)*) => {
+        $(
+            /// Identifies an output pair
+            pub struct $pair;
+
+            impl private::Sealed for $pair {}
+
+            impl Trait for $pair {
+                const ID: usize = $id;
+                type High = swm::$high;
+                type Low = swm::$low;
+            }
+        )*
+    };
+}
+
+pairs! {
+    Pair1, 0, SCT_OUT0, SCT_OUT1;
+    Pair2, 1, SCT_OUT2, SCT_OUT3;
+    Pair3, 2, SCT_OUT4, SCT_OUT5;
+}
+
+// Event allocation: The fault input uses two events, one for each counting
+// direction, as the outputs' set and clear conditions are reversed while the
+// counter counts down. Each pair uses two events, one for each of its
+// outputs. The limit is held by match register 0 and doesn't need an event.
+const FAULT_UP: usize = 0;
+const FAULT_DOWN: usize = 1;
+const FAULT_EVENTS: u8 = 0x1 << FAULT_UP | 0x1 << FAULT_DOWN;
+const PAIR_EVENT_BASE: usize = 2;
+
+/// A match value that is never reached, as it is above the limit
+const NEVER: u32 = u32::MAX;
+
+/// Configures the SCT for motor-control mode
+///
+/// Must be called once, when entering motor-control mode. Assumes that the
+/// SCT is in its reset state.
+pub(super) fn configure(sct: &pac::SCT0, period: u32) {
+    sct.config.modify(|_, w| {
+        w.unify().unified_counter();
+        w.clkmode().system_clock_mode();
+        w.autolimit_l().set_bit()
+    });
+
+    sct.ctrl.write(|w| {
+        w.halt_l().set_bit();
+        w.clrctr_l().set_bit();
+        w.bidir_l().up_down()
+    });
+
+    let limit = limit(period);
+    write_reg(MATCH, limit);
+    write_reg(MATCHREL, limit);
+
+    // The fault events are only enabled in `Timer::enable_fault_input`.
+    sct.event[FAULT_UP].ctrl.write(|w| {
+        w.outsel().input();
+        w.combmode().io();
+        w.direction().counting_up()
+    });
+    sct.event[FAULT_DOWN].ctrl.write(|w| {
+        w.outsel().input();
+        w.combmode().io();
+        w.direction().counting_down()
+    });
+    sct.halt
+        .write(|w| unsafe { w.haltmsk_l().bits(FAULT_EVENTS) });
+
+    configure_pair::<Pair1>(sct);
+    configure_pair::<Pair2>(sct);
+    configure_pair::<Pair3>(sct);
+
+    // All outputs are reversed while counting down, which means the match
+    // events switch them on while counting up and off while counting down
+    // (or vice versa).
+    sct.outputdirctrl.write(|w| {
+        w.setclr0().l_reversed();
+        w.setclr1().l_reversed();
+        w.setclr2().l_reversed();
+        w.setclr3().l_reversed();
+        w.setclr4().l_reversed();
+        w.setclr5().l_reversed()
+    });
+
+    // Conflicts can only arise between a match event and a fault event. Make
+    // sure the output is switched off in that case, taking into account the
+    // reversal of the high-side outputs' clear and the low-side outputs' set
+    // condition while counting down.
+    sct.res.write(|w| {
+        w.o0res().clear();
+        w.o1res().set();
+        w.o2res().clear();
+        w.o3res().set();
+        w.o4res().clear();
+        w.o5res().set()
+    });
+
+    sct.output.write(|w| unsafe { w.out().bits(0) });
+}
+
+fn configure_pair<P: Trait>(sct: &pac::SCT0) {
+    let high = PAIR_EVENT_BASE + 2 * P::ID;
+    let low = high + 1;
+
+    for &(event, match_reg) in
+        &[(high, high_match::<P>()), (low, low_match::<P>())]
+    {
+        write_reg(MATCH + 4 * match_reg, NEVER);
+        write_reg(MATCHREL + 4 * match_reg, NEVER);
+
+        sct.event[event]
+            .state
+            .write(|w| unsafe { w.statemskn().bits(0x1) });
+        sct.event[event].ctrl.write(|w| {
+            unsafe { w.matchsel().bits(match_reg as u8) };
+            w.combmode().match_()
+        });
+    }
+
+    let fault_up = 0x1 << FAULT_UP;
+    let fault_down = 0x1 << FAULT_DOWN;
+
+    // The high-side output is switched on by its match event while counting
+    // up. The low-side output is switched off by its match event while
+    // counting up. Both are switched off by the fault events.
+    let out = &sct.out[2 * P::ID];
+    out.set
+        .write(|w| unsafe { w.set().bits(0x1 << high | fault_down) });
+    out.clr.write(|w| unsafe { w.clr().bits(fault_up) });
+
+    let out = &sct.out[2 * P::ID + 1];
+    out.set.write(|w| unsafe { w.set().bits(fault_down) });
+    out.clr
+        .write(|w| unsafe { w.clr().bits(0x1 << low | fault_up) });
+}
+
+/// Converts a full PWM period into the counter limit
+fn limit(period: u32) -> u32 {
+    (period / 2).max(1)
+}
+
+/// The number of the match register for the high-side output of `P`
+fn high_match<P: Trait>() -> usize {
+    1 + 2 * P::ID
+}
+
+/// The number of the match register for the low-side output of `P`
+fn low_match<P: Trait>() -> usize {
+    2 + 2 * P::ID
+}
+
+fn read_reg(offset: usize) -> u32 {
+    // Sound, as the address is valid and properly aligned, and we're only
+    // reading.
+    unsafe { ptr::read_volatile(address(offset)) }
+}
+
+fn write_reg(offset: usize, value: u32) {
+    // Sound, as the address is valid and properly aligned. Every part of the
+    // motor-control API only writes the match registers it owns.
+    unsafe { ptr::write_volatile(address(offset), value) }
+}
+
+fn address(offset: usize) -> *mut u32 {
+    (pac::SCT0::ptr() as usize + offset) as *mut u32
+}
+
+fn sct() -> &'static pac::sct0::RegisterBlock {
+    // Sound, as the timer is the only part of the motor-control API that
+    // accesses the registers returned from here, apart from the pairs'
+    // atomic writes to their own match reload registers.
+    unsafe { &*pac::SCT0::ptr() }
+}
+
+mod private {
+    pub trait Sealed {}
+}
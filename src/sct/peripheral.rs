@@ -0,0 +1,271 @@
+use crate::{
+    init_state::{Disabled, Enabled},
+    pac, swm, syscon,
+};
+
+use super::{
+    gen::{configure_channels, Channels},
+    half::{H, L},
+    input,
+    motor::{self, MotorControl},
+    quadrature::{self, QuadratureDecoder},
+    timer::{self, Timer},
+};
+
+/// Interface to the State Configurable Timer (SCT)
+///
+/// Controls the SCT. Use [`Peripherals`] to gain access to an instance of
+/// this struct.
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [`Peripherals`]: ../struct.Peripherals.html
+/// [module documentation]: index.html
+pub struct SCT<State = Enabled> {
+    sct: pac::SCT0,
+    _state: State,
+}
+
+impl SCT<Disabled> {
+    pub(crate) fn new(sct: pac::SCT0) -> Self {
+        Self {
+            sct,
+            _state: Disabled,
+        }
+    }
+
+    /// Enable the SCT
+    ///
+    /// This method is only available, if `SCT` is in the [`Disabled`] state.
+    /// Code that attempts to call this method when the peripheral is already
+    /// enabled will not compile.
+    ///
+    /// Consumes this instance of `SCT` and returns another instance that has
+    /// its `State` type parameter set to [`Enabled`].
+    ///
+    /// [`Disabled`]: ../init_state/struct.Disabled.html
+    /// [`Enabled`]: ../init_state/struct.Enabled.html
+    pub fn enable(self, syscon: &mut syscon::Handle) -> SCT<Enabled> {
+        syscon.enable_clock(&self.sct);
+
+        SCT {
+            sct: self.sct,
+            _state: Enabled(()),
+        }
+    }
+}
+
+impl SCT<Enabled> {
+    /// Disable the SCT
+    ///
+    /// This method is only available, if `SCT` is in the [`Enabled`] state.
+    /// Code that attempts to call this method when the peripheral is already
+    /// disabled will not compile.
+    ///
+    /// Consumes this instance of `SCT` and returns another instance that has
+    /// its `State` type parameter set to [`Disabled`].
+    ///
+    /// [`Enabled`]: ../init_state/struct.Enabled.html
+    /// [`Disabled`]: ../init_state/struct.Disabled.html
+    pub fn disable(self, syscon: &mut syscon::Handle) -> SCT<Disabled> {
+        syscon.disable_clock(&self.sct);
+
+        SCT {
+            sct: self.sct,
+            _state: Disabled,
+        }
+    }
+
+    /// Split the SCT into two independent 16-bit timers
+    ///
+    /// Configures the SCT to operate as two 16-bit counters, L and H, and
+    /// returns a [`Timer`] and three PWM channels for each of them. Both timers
+    /// are halted, until they are started using [`Timer::start`].
+    ///
+    /// The PWM channels of counter L control the outputs `SCT_OUT0` to
+    /// `SCT_OUT2`, those of counter H control `SCT_OUT3` to `SCT_OUT5`.
+    ///
+    /// Assumes that the SCT is in its reset state.
+    pub fn split(self) -> Halves {
+        self.sct.config.modify(|_, w| {
+            w.unify().dual_counter();
+            w.clkmode().system_clock_mode()
+        });
+
+        // Halt and clear both counters.
+        self.sct.ctrl.write(|w| {
+            w.halt_l().set_bit();
+            w.clrctr_l().set_bit();
+            w.halt_h().set_bit();
+            w.clrctr_h().set_bit()
+        });
+
+        self.sct.limit.write(|w| unsafe {
+            w.limmsk_l().bits(timer::limit_event::<L>());
+            w.limmsk_h().bits(timer::limit_event::<H>())
+        });
+
+        timer::configure::<L>(&self.sct);
+        timer::configure::<H>(&self.sct);
+        configure_channels(&self.sct);
+
+        // If an output is set and cleared at the same time, set it. This is
+        // the case when the duty cycle is equal to the period.
+        self.sct.res.write(|w| {
+            w.o0res().set();
+            w.o1res().set();
+            w.o2res().set();
+            w.o3res().set();
+            w.o4res().set();
+            w.o5res().set()
+        });
+
+        Halves {
+            sct: self.sct,
+            timer_l: Timer::new(),
+            channels_l: Channels::new(),
+            timer_h: Timer::new(),
+            channels_h: Channels::new(),
+        }
+    }
+
+    /// Configure the SCT for motor control
+    ///
+    /// Configures the SCT to operate as a single 32-bit counter that counts up
+    /// and down, generating center-aligned PWM signals on three pairs of
+    /// complementary outputs. Please refer to the [`motor`] module for details.
+    ///
+    /// `period` is the length of a full PWM period, `dead_time` the time during
+    /// which both outputs of a pair are switched off, whenever one of them is
+    /// switched on. Both are measured in timer ticks, which are equal to system
+    /// clock cycles, unless the prescaler is changed using
+    /// [`motor::Timer::set_prescaler`]. The timer is halted, until it is
+    /// started using [`motor::Timer::start`].
+    ///
+    /// Assumes that the SCT is in its reset state.
+    ///
+    /// [`motor`]: super::motor
+    pub fn motor_control(self, period: u32, dead_time: u32) -> MotorControl {
+        motor::configure(&self.sct, period);
+        MotorControl::new(self.sct, dead_time)
+    }
+
+    /// Configure the SCT as a quadrature decoder
+    ///
+    /// Routes the given `SCT_PINn` functions to the SCT inputs with the same
+    /// number and configures the SCT to track the position of the encoder that
+    /// is connected to them. Please refer to the [`quadrature`] module for
+    /// details.
+    ///
+    /// Assumes that the SCT is in its reset state.
+    ///
+    /// [`quadrature`]: super::quadrature
+    pub fn quadrature_decoder<A, B, PinA, PinB>(
+        self,
+        _a: swm::Function<A, swm::state::Assigned<PinA>>,
+        _b: swm::Function<B, swm::state::Assigned<PinB>>,
+        inputmux: &pac::INPUTMUX,
+    ) -> QuadratureDecoder
+    where
+        A: input::Trait,
+        B: input::Trait,
+    {
+        quadrature::configure::<A, B>(&self.sct, inputmux);
+        QuadratureDecoder::new(self.sct)
+    }
+
+    /// Reset the SCT and return it
+    ///
+    /// Used to leave any of the modes the SCT can be configured for. Resets
+    /// the SCT via SYSCON, which halts all counters and returns all registers
+    /// and outputs to their reset state. This is the state the SCT needs to be
+    /// in, to configure a mode.
+    pub(super) fn reset(sct: pac::SCT0, syscon: &mut syscon::Handle) -> Self {
+        syscon.assert_reset(&sct);
+        syscon.clear_reset(&sct);
+
+        Self {
+            sct,
+            _state: Enabled(()),
+        }
+    }
+}
+
+impl<State> SCT<State> {
+    /// Return the raw peripheral
+    ///
+    /// This method serves as an escape hatch from the HAL API. It returns the
+    /// raw peripheral, allowing you to do whatever you want with it, without
+    /// limitations imposed by the API.
+    ///
+    /// If you are using this method because a feature you need is missing from
+    /// the HAL API, please [open an issue] or, if an issue for your feature
+    /// request already exists, comment on the existing issue, so we can
+    /// prioritize it accordingly.
+    ///
+    /// [open an issue]: https://github.com/lpc-rs/lpc8xx-hal/issues
+    pub fn free(self) -> pac::SCT0 {
+        self.sct
+    }
+}
+
+/// The SCT, split into two independent 16-bit timers
+///
+/// Returned by [`SCT::split`]. Use [`Halves::reset`] to get the [`SCT`] back.
+pub struct Halves {
+    sct: pac::SCT0,
+
+    /// The timer that uses counter L
+    pub timer_l: Timer<L>,
+
+    /// The PWM channels of counter L
+    pub channels_l: Channels<L>,
+
+    /// The timer that uses counter H
+    pub timer_h: Timer<H>,
+
+    /// The PWM channels of counter H
+    pub channels_h: Channels<H>,
+}
+
+impl Halves {
+    /// Reset the SCT and leave split mode
+    ///
+    /// Consumes the timers and channels of both halves. Channels that have
+    /// been attached need to be detached using [`Channel::detach`] first.
+    /// Resets the SCT, which stops both timers and returns all outputs to
+    /// their reset state.
+    ///
+    /// # Example
+    ///
+    /// ``` no_run
+    /// use lpc8xx_hal::{prelude::*, Peripherals};
+    ///
+    /// let p = Peripherals::take().unwrap();
+    ///
+    /// let mut syscon = p.SYSCON.split();
+    /// let mut swm = p.SWM.split();
+    ///
+    /// #[cfg(feature = "82x")]
+    /// let mut swm_handle = swm.handle;
+    /// #[cfg(feature = "845")]
+    /// let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+    ///
+    /// let (pwm_output, _) = swm.movable_functions.sct_out0.assign(
+    ///     p.pins.pio0_17.into_swm_pin(),
+    ///     &mut swm_handle,
+    /// );
+    ///
+    /// let mut sct = p.SCT0.enable(&mut syscon.handle).split();
+    /// let pwm = sct.channels_l.channel1.attach(pwm_output);
+    ///
+    /// // Put the detached channel back, then leave split mode.
+    /// sct.channels_l.channel1 = pwm.detach();
+    /// let sct = sct.reset(&mut syscon.handle);
+    /// ```
+    ///
+    /// [`Channel::detach`]: super::Channel::detach
+    pub fn reset(self, syscon: &mut syscon::Handle) -> SCT<Enabled> {
+        SCT::reset(self.sct, syscon)
+    }
+}
@@ -0,0 +1,500 @@
+//! Quadrature decoder
+//!
+//! Decodes the signals of a rotary or linear encoder, using the SCT state
+//! machine. Once configured, the position is tracked entirely in hardware,
+//! without any CPU involvement per edge.
+//!
+//! The decoder counts one step per full cycle of the quadrature signals (x1
+//! decoding). A rising edge on input A while input B is low is a step forward,
+//! a falling edge on input A while input B is low is a step backward. This
+//! means jitter around an edge, or a reversal of direction, can't accumulate
+//! counts.
+//!
+//! Both halves of the SCT counter are clocked by rising edges of input A:
+//! counter L counts steps in the forward direction, counter H counts steps in
+//! the backward direction. As a falling edge can't be counted directly, a step
+//! backward is recorded in the state of the state machine, and settled on the
+//! next rising edge of input A. Please refer to [`Model`], which models the
+//! state machine and can be used on the host, for details.
+//!
+//! As both counters are only 16 bits wide, the position must be read at least
+//! once every 32767 steps, to keep track of overflows. This is done
+//! transparently by [`QuadratureDecoder::position`].
+//!
+//! Use [`SCT::quadrature_decoder`] to enter this mode.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::Peripherals;
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let mut syscon = p.SYSCON.split();
+//! let mut swm = p.SWM.split();
+//!
+//! #[cfg(feature = "82x")]
+//! let mut swm_handle = swm.handle;
+//! #[cfg(feature = "845")]
+//! let mut swm_handle = swm.handle.enable(&mut syscon.handle);
+//!
+//! let (a, _) = swm.movable_functions.sct_pin0.assign(
+//!     p.pins.pio0_16.into_swm_pin(),
+//!     &mut swm_handle,
+//! );
+//! let (b, _) = swm.movable_functions.sct_pin1.assign(
+//!     p.pins.pio0_17.into_swm_pin(),
+//!     &mut swm_handle,
+//! );
+//!
+//! let mut encoder = p
+//!     .SCT0
+//!     .enable(&mut syscon.handle)
+//!     .quadrature_decoder(a, b, &p.INPUTMUX);
+//!
+//! loop {
+//!     let position = encoder.position();
+//!     // do something with the position
+//! }
+//! ```
+//!
+//! [`SCT::quadrature_decoder`]: super::SCT::quadrature_decoder
+
+use crate::{init_state::Enabled, pac, swm, syscon};
+
+use super::{
+    half::{self, COUNT, H, L, MATCH, MATCHREL},
+    input, SCT,
+};
+
+/// A quadrature decoder based on the SCT
+///
+/// Returned by [`SCT::quadrature_decoder`]. Please refer to the [module
+/// documentation] for details. Use [`QuadratureDecoder::reset`] to get the
+/// [`SCT`] back.
+///
+/// [`SCT::quadrature_decoder`]: super::SCT::quadrature_decoder
+/// [`SCT`]: super::SCT
+/// [module documentation]: index.html
+pub struct QuadratureDecoder {
+    sct: pac::SCT0,
+    position: i32,
+    direction: Direction,
+    forward: u16,
+    backward: u16,
+}
+
+impl QuadratureDecoder {
+    pub(super) fn new(sct: pac::SCT0) -> Self {
+        Self {
+            sct,
+            position: 0,
+            direction: Direction::Forward,
+            forward: 0,
+            backward: 0,
+        }
+    }
+
+    /// Returns the current position
+    ///
+    /// The position is measured in steps, starting at zero, and increases when
+    /// moving forward. It wraps around on overflow.
+    ///
+    /// Must be called at least once every 32767 steps, as the hardware
+    /// counters would overflow undetectably otherwise.
+    pub fn position(&mut self) -> i32 {
+        let owed = self.update();
+        self.position.wrapping_sub(owed as i32)
+    }
+
+    /// Sets the current position
+    pub fn set_position(&mut self, position: i32) {
+        let owed = self.update();
+        self.position = position.wrapping_add(owed as i32);
+    }
+
+    /// Updates the position from the counters
+    ///
+    /// Returns whether a step backward is owed, which is not reflected in
+    /// `self.position` yet.
+    fn update(&mut self) -> bool {
+        // The state determines whether a step is owed. Make sure it's
+        // consistent with the counters, in case of an edge while reading.
+        let (state, forward, backward) = loop {
+            let before = sct().state.read().state_l().bits();
+            let forward = half::read::<L>(COUNT);
+            let backward = half::read::<H>(COUNT);
+            let after = sct().state.read().state_l().bits();
+
+            if before == after {
+                break (after, forward, backward);
+            }
+        };
+
+        let forward_steps = forward.wrapping_sub(self.forward);
+        let backward_steps = backward.wrapping_sub(self.backward);
+
+        if forward_steps > backward_steps {
+            self.direction = Direction::Forward;
+        } else if backward_steps > forward_steps {
+            self.direction = Direction::Backward;
+        }
+
+        self.forward = forward;
+        self.backward = backward;
+        self.position = self
+            .position
+            .wrapping_add(forward_steps as i32)
+            .wrapping_sub(backward_steps as i32);
+
+        is_owed(state)
+    }
+
+    /// Returns the direction of the last movement
+    ///
+    /// This is based on the steps that have been counted since the last call
+    /// to [`QuadratureDecoder::position`], which is called by this method.
+    pub fn direction(&mut self) -> Direction {
+        self.position();
+        self.direction
+    }
+
+    /// Enable the index input
+    ///
+    /// Routes the given `SCT_PINn` function to SCT input `n`. From now on, the
+    /// position is captured in hardware on each rising edge of the index
+    /// signal. Use [`QuadratureDecoder::index_position`] to read it.
+    pub fn enable_index<I, Pin>(
+        &mut self,
+        _: swm::Function<I, swm::state::Assigned<Pin>>,
+        inputmux: &pac::INPUTMUX,
+    ) where
+        I: input::Trait,
+    {
+        input::select::<I>(inputmux);
+
+        let event = &sct().event[INDEX];
+        event.ctrl.write(|w| {
+            unsafe { w.iosel().bits(I::ID) };
+            w.outsel().input();
+            w.iocond().rise();
+            w.combmode().io()
+        });
+        event
+            .state
+            .write(|w| unsafe { w.statemskn().bits(ALL_STATES) });
+    }
+
+    /// Returns the position at the last index pulse
+    ///
+    /// Returns `None`, if no index pulse has occurred since this method was
+    /// last called. If several pulses have occurred, the position at the most
+    /// recent one is returned.
+    ///
+    /// Like [`QuadratureDecoder::position`], this method must be called before
+    /// the hardware counters overflow, after an index pulse has occurred.
+    ///
+    /// The index pulse is expected to occur while input A is high, as is the
+    /// case for most encoders. Otherwise, the captured position might be off
+    /// by one step, if a step backward was owed at the time.
+    pub fn index_position(&mut self) -> Option<i32> {
+        if sct().evflag.read().flag().bits() & 0x1 << INDEX == 0 {
+            return None;
+        }
+        sct()
+            .evflag
+            .write(|w| unsafe { w.flag().bits(0x1 << INDEX) });
+
+        self.update();
+        let position = self.position;
+
+        let forward = half::read::<L>(MATCH);
+        let backward = half::read::<H>(MATCH);
+
+        // Steps that have been counted since the index pulse
+        let forward_steps = self.forward.wrapping_sub(forward);
+        let backward_steps = self.backward.wrapping_sub(backward);
+
+        Some(
+            position
+                .wrapping_sub(forward_steps as i32)
+                .wrapping_add(backward_steps as i32),
+        )
+    }
+
+    /// Reset the SCT and leave quadrature decoder mode
+    ///
+    /// Resets the SCT, which stops both counters. The input functions stay
+    /// assigned to their pins.
+    pub fn reset(self, syscon: &mut syscon::Handle) -> SCT<Enabled> {
+        SCT::reset(self.sct, syscon)
+    }
+}
+
+/// The direction of movement detected by the quadrature decoder
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Direction {
+    /// Moving forward, meaning input A leads input B
+    Forward,
+
+    /// Moving backward, meaning input B leads input A
+    Backward,
+}
+
+/// An edge on one of the inputs of the quadrature decoder
+///
+/// Used by [`Model`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Edge {
+    /// A rising edge on input A
+    ARise,
+
+    /// A falling edge on input A
+    AFall,
+
+    /// A rising edge on input B
+    BRise,
+
+    /// A falling edge on input B
+    BFall,
+}
+
+/// A model of the quadrature decoder's state machine
+///
+/// The SCT is configured from the same transition table that this model uses,
+/// so the model can be used to check the decoding on the host.
+///
+/// # Example
+///
+/// ```
+/// use lpc8xx_hal::sct::quadrature::{Edge::*, Model};
+///
+/// // Start with both inputs low.
+/// let mut model = Model::new(false);
+///
+/// // One step forward
+/// for &edge in &[ARise, BRise, AFall, BFall] {
+///     model.edge(edge);
+/// }
+/// assert_eq!(model.position(), 1);
+///
+/// // Reverse direction halfway through the next step, then go back.
+/// for &edge in &[ARise, BRise, AFall, ARise, BFall, AFall] {
+///     model.edge(edge);
+/// }
+/// assert_eq!(model.position(), 1);
+///
+/// // Jitter on input A doesn't accumulate counts.
+/// for _ in 0..10 {
+///     model.edge(ARise);
+///     assert_eq!(model.position(), 2);
+///     model.edge(AFall);
+///     assert_eq!(model.position(), 1);
+/// }
+///
+/// // Two steps backward
+/// for _ in 0..2 {
+///     for &edge in &[BRise, ARise, BFall, AFall] {
+///         model.edge(edge);
+///     }
+/// }
+/// assert_eq!(model.position(), -1);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Model {
+    state: u8,
+    forward: i32,
+    backward: i32,
+}
+
+impl Model {
+    /// Create a new model, in the initial state of the decoder
+    ///
+    /// `b_high` is the level of input B when the decoder is configured.
+    pub fn new(b_high: bool) -> Self {
+        Self {
+            state: initial_state(b_high),
+            forward: 0,
+            backward: 0,
+        }
+    }
+
+    /// Process an edge on one of the inputs
+    pub fn edge(&mut self, edge: Edge) {
+        // A counter counts the edge, if it's running in the state before the
+        // edge.
+        if edge == Edge::ARise {
+            if counts_forward(self.state) {
+                self.forward = self.forward.wrapping_add(1);
+            }
+            if counts_backward(self.state) {
+                self.backward = self.backward.wrapping_add(1);
+            }
+        }
+
+        for &(e, from, to) in &TRANSITIONS {
+            if e == edge && from == self.state {
+                self.state = to;
+                break;
+            }
+        }
+    }
+
+    /// Returns the position, as [`QuadratureDecoder::position`] would
+    pub fn position(&self) -> i32 {
+        self.forward
+            .wrapping_sub(self.backward)
+            .wrapping_sub(is_owed(self.state) as i32)
+    }
+}
+
+// States of the state machine. Input B determines whether a step in the
+// forward or backward direction can be counted. A falling edge on input A
+// while input B is low is a step backward, which is owed until the next rising
+// edge on input A. If input B is high then, counter H counts it. If input B is
+// low, it cancels out the step forward, and nothing is counted.
+const B_LOW: u8 = 0;
+const B_HIGH: u8 = 1;
+const B_LOW_OWED: u8 = 2;
+const B_HIGH_OWED: u8 = 3;
+
+const ALL_STATES: u8 = 0x0f;
+
+// The transitions of the state machine. Each of them is an event, with the
+// same index as in this table.
+const TRANSITIONS: [(Edge, u8, u8); 7] = [
+    (Edge::BRise, B_LOW, B_HIGH),
+    (Edge::BRise, B_LOW_OWED, B_HIGH_OWED),
+    (Edge::BFall, B_HIGH, B_LOW),
+    (Edge::BFall, B_HIGH_OWED, B_LOW_OWED),
+    (Edge::AFall, B_LOW, B_LOW_OWED),
+    (Edge::ARise, B_LOW_OWED, B_LOW),
+    (Edge::ARise, B_HIGH_OWED, B_HIGH),
+];
+
+// The event that captures the position on an index pulse
+const INDEX: usize = TRANSITIONS.len();
+
+fn initial_state(b_high: bool) -> u8 {
+    if b_high {
+        B_HIGH
+    } else {
+        B_LOW
+    }
+}
+
+/// Indicates whether counter L runs in the given state
+fn counts_forward(state: u8) -> bool {
+    state == B_LOW
+}
+
+/// Indicates whether counter H runs in the given state
+fn counts_backward(state: u8) -> bool {
+    state == B_HIGH_OWED
+}
+
+/// Indicates whether a step backward is owed in the given state
+fn is_owed(state: u8) -> bool {
+    state == B_LOW_OWED || state == B_HIGH_OWED
+}
+
+/// Configures the SCT as a quadrature decoder
+///
+/// Must be called once, when entering quadrature decoder mode. Assumes that
+/// the SCT is in its reset state.
+pub(super) fn configure<A, B>(sct: &pac::SCT0, inputmux: &pac::INPUTMUX)
+where
+    A: input::Trait,
+    B: input::Trait,
+{
+    input::select::<A>(inputmux);
+    input::select::<B>(inputmux);
+
+    // Both counters are clocked by rising edges on input A.
+    sct.config.modify(|_, w| {
+        w.unify().dual_counter();
+        w.clkmode().sampled_system_clock_mode();
+        unsafe { w.cksel().bits(2 * A::ID) }
+    });
+
+    // Each counter is started by the transitions into the state it runs in,
+    // and stopped by the transitions out of it.
+    let mut start_l = 0;
+    let mut stop_l = 0;
+    let mut start_h = 0;
+    let mut stop_h = 0;
+
+    for (event, &(edge, from, to)) in TRANSITIONS.iter().enumerate() {
+        let (input, rise) = match edge {
+            Edge::ARise => (A::ID, true),
+            Edge::AFall => (A::ID, false),
+            Edge::BRise => (B::ID, true),
+            Edge::BFall => (B::ID, false),
+        };
+
+        let ev = &sct.event[event];
+        ev.ctrl.write(|w| {
+            unsafe {
+                w.iosel().bits(input);
+                w.statev().bits(to);
+            }
+            w.outsel().input();
+            if rise {
+                w.iocond().rise();
+            } else {
+                w.iocond().fall();
+            }
+            w.combmode().io();
+            w.stateld().load()
+        });
+        ev.state.write(|w| unsafe { w.statemskn().bits(1 << from) });
+
+        let mask = 1 << event;
+        match (counts_forward(from), counts_forward(to)) {
+            (false, true) => start_l |= mask,
+            (true, false) => stop_l |= mask,
+            _ => {}
+        }
+        match (counts_backward(from), counts_backward(to)) {
+            (false, true) => start_h |= mask,
+            (true, false) => stop_h |= mask,
+            _ => {}
+        }
+    }
+
+    sct.start.write(|w| unsafe {
+        w.startmsk_l().bits(start_l);
+        w.startmsk_h().bits(start_h)
+    });
+    sct.stop.write(|w| unsafe {
+        w.stopmsk_l().bits(stop_l);
+        w.stopmsk_h().bits(stop_h)
+    });
+
+    // The index event captures both counters into capture register 0.
+    sct.regmode.write(|w| unsafe {
+        w.regmod_l().bits(0x1);
+        w.regmod_h().bits(0x1)
+    });
+    half::write::<L>(MATCHREL, 0x1 << INDEX);
+    half::write::<H>(MATCHREL, 0x1 << INDEX);
+
+    // Select the initial state, based on the current level of input B.
+    let b_high = sct.input.read().bits() & 0x1 << (16 + B::ID) != 0;
+    let state = initial_state(b_high);
+    sct.state.write(|w| unsafe { w.state_l().bits(state) });
+
+    sct.ctrl.write(|w| {
+        w.clrctr_l().set_bit();
+        w.clrctr_h().set_bit();
+        w.stop_l().bit(!counts_forward(state));
+        w.stop_h().bit(!counts_backward(state))
+    });
+}
+
+fn sct() -> &'static pac::sct0::RegisterBlock {
+    // Sound, as `QuadratureDecoder` is the only user of the SCT registers once
+    // the decoder has been configured, and it requires `&mut self` for all
+    // modifications.
+    unsafe { &*pac::SCT0::ptr() }
+}
@@ -0,0 +1,196 @@
+use core::marker::PhantomData;
+
+use embedded_hal::timer::{CountDown, Periodic};
+use embedded_hal_alpha::timer::{
+    CountDown as CountDownAlpha, Periodic as PeriodicAlpha,
+};
+use void::Void;
+
+use crate::pac;
+
+use super::half::{self, CTRL, CTRL_CLRCTR, CTRL_HALT, CTRL_PRE_MASK};
+
+/// One half of the SCT, operating as an independent 16-bit timer
+///
+/// Use [`SCT::split`] to gain access to instances of this struct.
+///
+/// The timer counts up from zero, until it reaches the end of its period, then
+/// starts over. The period is set using [`Timer::start`] and is also used as
+/// the period of the PWM channels that belong to the same half.
+///
+/// The timer is clocked by the system clock, divided by the prescaler (see
+/// [`Timer::set_prescaler`]). This means it runs at 12_000_000 ticks per
+/// second by default, if you haven't changed the system clock.
+///
+/// # `embedded-hal` traits
+/// - [`embedded_hal::timer::CountDown`]
+/// - [`embedded_hal::timer::Periodic`]
+///
+/// [`SCT::split`]: super::SCT::split
+/// [`embedded_hal::timer::CountDown`]: #impl-CountDown
+/// [`embedded_hal::timer::Periodic`]: #impl-Periodic
+pub struct Timer<H> {
+    _half: PhantomData<H>,
+}
+
+impl<H> Timer<H>
+where
+    H: half::Trait,
+{
+    pub(super) fn new() -> Self {
+        Self { _half: PhantomData }
+    }
+
+    /// Set the prescaler of this timer
+    ///
+    /// The timer will be clocked by the system clock, divided by
+    /// `prescaler + 1`. Takes effect immediately, even if the timer is already
+    /// running.
+    pub fn set_prescaler(&mut self, prescaler: u8) {
+        let ctrl = half::read::<H>(CTRL) & !CTRL_PRE_MASK;
+        half::write::<H>(CTRL, ctrl | pre(prescaler));
+    }
+
+    /// Start the timer
+    ///
+    /// Resets the counter and starts it with a period of `ticks`. If `ticks` is
+    /// zero, a period of one tick is used instead.
+    ///
+    /// The period also applies to the PWM channels of this half. Changes to
+    /// their duty cycle take effect at the end of a period.
+    pub fn start(&mut self, ticks: u16) {
+        let pre = half::read::<H>(CTRL) & CTRL_PRE_MASK;
+
+        // Halt the counter and reset it, to prevent race conditions when
+        // resetting the event flag.
+        half::write::<H>(CTRL, CTRL_HALT | CTRL_CLRCTR | pre);
+
+        let limit = ticks.saturating_sub(1);
+        half::write::<H>(half::MATCH, limit);
+        half::write::<H>(half::MATCHREL, limit);
+
+        self.clear_limit_flag();
+
+        half::write::<H>(CTRL, pre);
+    }
+
+    /// Stop the timer
+    ///
+    /// The counter keeps its current value, until the timer is started again.
+    pub fn stop(&mut self) {
+        let ctrl = half::read::<H>(CTRL);
+        half::write::<H>(CTRL, ctrl | CTRL_HALT);
+    }
+
+    /// Indicates whether the timer is running
+    pub fn is_running(&self) -> bool {
+        half::read::<H>(CTRL) & CTRL_HALT == 0
+    }
+
+    /// Returns the current counter value
+    pub fn value(&self) -> u16 {
+        half::read::<H>(half::COUNT)
+    }
+
+    /// Non-blockingly "waits" until the end of the current period
+    fn wait(&mut self) -> nb::Result<(), Void> {
+        // Sound, as we're only reading.
+        let sct = unsafe { &*pac::SCT0::ptr() };
+
+        if sct.evflag.read().flag().bits() & limit_event::<H>() != 0 {
+            self.clear_limit_flag();
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+
+    fn clear_limit_flag(&mut self) {
+        // Sound, as writing zeros to EVFLAG has no effect, which means we're
+        // only touching the flag that belongs to this timer.
+        let sct = unsafe { &*pac::SCT0::ptr() };
+
+        sct.evflag
+            .write(|w| unsafe { w.flag().bits(limit_event::<H>()) });
+    }
+}
+
+impl<H> CountDown for Timer<H>
+where
+    H: half::Trait,
+{
+    /// The timer operates in ticks of the prescaled system clock
+    type Time = u16;
+
+    fn start<Time>(&mut self, count: Time)
+    where
+        Time: Into<Self::Time>,
+    {
+        self.start(count.into());
+    }
+
+    fn wait(&mut self) -> nb::Result<(), Void> {
+        self.wait()
+    }
+}
+
+impl<H> CountDownAlpha for Timer<H>
+where
+    H: half::Trait,
+{
+    type Error = Void;
+
+    /// The timer operates in ticks of the prescaled system clock
+    type Time = u16;
+
+    fn try_start<Time>(&mut self, count: Time) -> Result<(), Self::Error>
+    where
+        Time: Into<Self::Time>,
+    {
+        self.start(count.into());
+        Ok(())
+    }
+
+    fn try_wait(&mut self) -> nb::Result<(), Self::Error> {
+        self.wait()
+    }
+}
+
+impl<H> Periodic for Timer<H> where H: half::Trait {}
+
+impl<H> PeriodicAlpha for Timer<H> where H: half::Trait {}
+
+/// Configures the events and outputs that belong to a counter half
+///
+/// Must be called once for each half, when the SCT is split.
+pub(super) fn configure<H>(sct: &pac::SCT0)
+where
+    H: half::Trait,
+{
+    // The limit event, followed by the events for the three PWM channels. Each
+    // of these is triggered by the match register with the same relative
+    // index.
+    for i in 0..4 {
+        let event = &sct.event[H::EVENT_BASE + i];
+
+        // Enable the event in state 0, which is the only state we use.
+        event.state.write(|w| unsafe { w.statemskn().bits(0x1) });
+        event.ctrl.write(|w| {
+            unsafe { w.matchsel().bits(i as u8) };
+            w.hevent().bit(H::HEVENT);
+            w.combmode().match_()
+        });
+    }
+}
+
+/// The bit mask of the limit event of the given half
+pub(super) fn limit_event<H>() -> u8
+where
+    H: half::Trait,
+{
+    0x1 << H::EVENT_BASE
+}
+
+fn pre(prescaler: u8) -> u16 {
+    (prescaler as u16) << half::CTRL_PRE_SHIFT
+}
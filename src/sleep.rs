@@ -3,11 +3,15 @@
 //! This module provides a higher-level API layer that can be used to put the
 //! microcontroller to sleep for a given amount of time.
 //!
-//! Both sleeping via busy waiting and via regular sleep mode are supported.
-//! Please refer to [`sleep::Busy`] and [`sleep::Regular`] for more details.
+//! Sleeping via busy waiting, regular sleep mode, deep-sleep mode, and
+//! power-down mode are supported. Please refer to [`sleep::Busy`],
+//! [`sleep::Regular`], [`sleep::DeepSleep`], and [`sleep::PowerDown`] for more
+//! details.
 //!
 //! [`sleep::Busy`]: struct.Busy.html
 //! [`sleep::Regular`]: struct.Regular.html
+//! [`sleep::DeepSleep`]: struct.DeepSleep.html
+//! [`sleep::PowerDown`]: struct.PowerDown.html
 
 use cortex_m::{asm, interrupt};
 use embedded_hal::prelude::*;
@@ -15,8 +19,10 @@ use nb;
 
 use crate::{
     clock::{self, Ticks},
+    init_state,
     pac::{self, Interrupt, NVIC},
-    pmu,
+    pmu::{self, LowPowerClock},
+    syscon::{self, IoscDerivedClock, WktWakeup},
     wkt::{self, WKT},
 };
 
@@ -41,7 +47,9 @@ where
 /// to measure the time. An interrupt handler is not required.
 ///
 /// Only clocks that the WKT supports can be used. See [`wkt::Clock`] for more
-/// details.
+/// details. The clock of the ticks passed to [`Sleep::sleep`] is selected for
+/// the duration of the sleep. The clock selected via [`WKT::select_clock`] is
+/// restored afterwards.
 ///
 /// Since this sleep mode waits busily, which is very energy-inefficient, it is
 /// only suitable for simple examples, or very short wait times.
@@ -68,11 +76,11 @@ where
 /// let delay = Ticks { value: 750_000, clock: &clock }; // 1000 ms
 /// sleep.sleep(delay);
 /// ```
-pub struct Busy<'wkt> {
-    wkt: &'wkt mut WKT,
+pub struct Busy<'wkt, C = IoscDerivedClock> {
+    wkt: &'wkt mut WKT<init_state::Enabled, C>,
 }
 
-impl<'wkt> Busy<'wkt> {
+impl<'wkt, C> Busy<'wkt, C> {
     /// Prepare busy sleep mode
     ///
     /// Returns an instance of `sleep::Busy`, which implements [`Sleep`] and can
@@ -81,12 +89,12 @@ impl<'wkt> Busy<'wkt> {
     /// Requires a mutable reference to [`WKT`]. The reference will be borrowed
     /// for as long as the `sleep::Busy` instance exists, as it will be needed
     /// to count down the time in every call to [`Sleep::sleep`].
-    pub fn prepare(wkt: &'wkt mut WKT) -> Self {
+    pub fn prepare(wkt: &'wkt mut WKT<init_state::Enabled, C>) -> Self {
         Busy { wkt }
     }
 }
 
-impl<'wkt, Clock> Sleep<Clock> for Busy<'wkt>
+impl<'wkt, C, Clock> Sleep<Clock> for Busy<'wkt, C>
 where
     Clock: clock::Enabled + wkt::Clock,
 {
@@ -102,10 +110,12 @@ where
             return;
         }
 
-        self.wkt.start(ticks.value);
-        while let Err(nb::Error::WouldBlock) = self.wkt.wait() {
-            asm::nop();
-        }
+        self.wkt.with_clock::<Clock, _>(|wkt| {
+            wkt.start(ticks.value);
+            while let Err(nb::Error::WouldBlock) = wkt.wait() {
+                asm::nop();
+            }
+        });
     }
 }
 
@@ -113,7 +123,10 @@ where
 ///
 /// Provides a [`Sleep`] implementation for the regular sleep mode and uses the
 /// [WKT] to wake the microcontroller up again, at the right time. Only clocks
-/// that the WKT supports can be used. See [`wkt::Clock`] for more details.
+/// that the WKT supports can be used. See [`wkt::Clock`] for more details. The
+/// clock of the ticks passed to [`Sleep::sleep`] is selected for the duration
+/// of the sleep. The clock selected via [`WKT::select_clock`] is restored
+/// afterwards.
 ///
 /// # Examples
 ///
@@ -146,13 +159,13 @@ where
 /// // This will put the microcontroller into sleep mode.
 /// sleep.sleep(delay);
 /// ```
-pub struct Regular<'r> {
+pub struct Regular<'r, C = IoscDerivedClock> {
     pmu: &'r mut pmu::Handle,
     scb: &'r mut pac::SCB,
-    wkt: &'r mut WKT,
+    wkt: &'r mut WKT<init_state::Enabled, C>,
 }
 
-impl<'r> Regular<'r> {
+impl<'r, C> Regular<'r, C> {
     /// Prepare regular sleep mode
     ///
     /// Returns an instance of `sleep::Regular`, which implements [`Sleep`] and
@@ -164,13 +177,13 @@ impl<'r> Regular<'r> {
     pub fn prepare(
         pmu: &'r mut pmu::Handle,
         scb: &'r mut pac::SCB,
-        wkt: &'r mut WKT,
+        wkt: &'r mut WKT<init_state::Enabled, C>,
     ) -> Self {
         Regular { pmu, scb, wkt }
     }
 }
 
-impl<'r, Clock> Sleep<Clock> for Regular<'r>
+impl<'r, C, Clock> Sleep<Clock> for Regular<'r, C>
 where
     Clock: clock::Enabled + wkt::Clock,
 {
@@ -186,27 +199,221 @@ where
             return;
         }
 
-        self.wkt.select_clock::<Clock>();
-        self.wkt.start(ticks.value);
-
-        // Within the this closure, interrupts are enabled, but interrupt
-        // handlers won't run. This means that we'll exit sleep mode when the
-        // WKT interrupt is fired, but there won't be an interrupt handler that
-        // will require the WKT's alarm flag to be reset. This means the `wait`
-        // method can use the alarm flag, which would otherwise need to be reset
-        // to exit the interrupt handler.
-        interrupt::free(|_| {
-            // Safe, because this is not going to interfere with the critical
-            // section.
-            unsafe { NVIC::unmask(Interrupt::WKT) };
-
-            while let Err(nb::Error::WouldBlock) = self.wkt.wait() {
-                self.pmu.enter_sleep_mode(self.scb);
-            }
+        let pmu = &mut *self.pmu;
+        let scb = &mut *self.scb;
+
+        self.wkt.with_clock::<Clock, _>(|wkt| {
+            wkt.start(ticks.value);
+
+            // Within the this closure, interrupts are enabled, but interrupt
+            // handlers won't run. This means that we'll exit sleep mode when
+            // the WKT interrupt is fired, but there won't be an interrupt
+            // handler that will require the WKT's alarm flag to be reset. This
+            // means the `wait` method can use the alarm flag, which would
+            // otherwise need to be reset to exit the interrupt handler.
+            interrupt::free(|_| {
+                // Safe, because this is not going to interfere with the
+                // critical section.
+                unsafe { NVIC::unmask(Interrupt::WKT) };
 
-            // If we don't do this, the (possibly non-existing) interrupt
-            // handler will be called as soon as we exit this closure.
-            NVIC::mask(Interrupt::WKT);
+                while let Err(nb::Error::WouldBlock) = wkt.wait() {
+                    pmu.enter_sleep_mode(scb);
+                }
+
+                // If we don't do this, the (possibly non-existing) interrupt
+                // handler will be called as soon as we exit this closure.
+                NVIC::mask(Interrupt::WKT);
+            });
         });
     }
 }
+
+/// Deep-sleep mode
+///
+/// Provides a [`Sleep`] implementation for deep-sleep mode and uses the [WKT]
+/// to wake the microcontroller up again, at the right time. In deep-sleep mode,
+/// the clocks of the core and all peripherals are stopped, so the WKT must be
+/// running on the [`LowPowerClock`].
+///
+/// The WKT interrupt is enabled as a wake-up source in STARTERP1, when
+/// preparing this sleep mode. Before entering deep-sleep mode, the IRC/FRO is
+/// selected as the main clock, as required by the hardware, and PDAWAKECFG is
+/// configured to match the current power configuration. The previous main
+/// clock is restored after wake-up.
+///
+/// # Examples
+///
+/// ``` no_run
+/// use lpc8xx_hal::{
+///     prelude::*,
+///     Peripherals,
+///     clock::Ticks,
+///     pac::CorePeripherals,
+///     sleep,
+/// };
+///
+/// let mut cp = CorePeripherals::take().unwrap();
+/// let mut p = Peripherals::take().unwrap();
+///
+/// let mut pmu    = p.PMU.split();
+/// let mut syscon = p.SYSCON.split();
+///
+/// let clock = pmu.low_power_clock.enable(&mut pmu.handle);
+/// let mut wkt = p.WKT.enable(&mut syscon.handle).select_clock(&clock);
+///
+/// let mut sleep = sleep::DeepSleep::prepare(
+///     &mut pmu.handle,
+///     &mut cp.SCB,
+///     &mut syscon.handle,
+///     &mut wkt,
+/// );
+///
+/// let delay = Ticks { value: 10_000, clock: &clock }; // 1000 ms
+///
+/// // This will put the microcontroller into deep-sleep mode.
+/// sleep.sleep(delay);
+/// ```
+pub struct DeepSleep<'r, 'clock> {
+    pmu: &'r mut pmu::Handle,
+    scb: &'r mut pac::SCB,
+    syscon: &'r mut syscon::Handle,
+    wkt: &'r mut WKT<init_state::Enabled, &'clock LowPowerClock>,
+}
+
+impl<'r, 'clock> DeepSleep<'r, 'clock> {
+    /// Prepare deep-sleep mode
+    ///
+    /// Returns an instance of `sleep::DeepSleep`, which implements [`Sleep`]
+    /// and can therefore be used to put the microcontroller to sleep.
+    ///
+    /// Requires references to various peripherals, which will be borrowed for
+    /// as long as the `sleep::DeepSleep` instance exists, as they will be
+    /// needed for every call to [`Sleep::sleep`].
+    pub fn prepare(
+        pmu: &'r mut pmu::Handle,
+        scb: &'r mut pac::SCB,
+        syscon: &'r mut syscon::Handle,
+        wkt: &'r mut WKT<init_state::Enabled, &'clock LowPowerClock>,
+    ) -> Self {
+        syscon.enable_interrupt_wakeup::<WktWakeup>();
+
+        DeepSleep {
+            pmu,
+            scb,
+            syscon,
+            wkt,
+        }
+    }
+}
+
+impl<'r, 'clock> Sleep<LowPowerClock> for DeepSleep<'r, 'clock> {
+    fn sleep<'c, T>(&mut self, ticks: T)
+    where
+        LowPowerClock: 'c,
+        T: Into<Ticks<'c, LowPowerClock>>,
+    {
+        let pmu = &mut *self.pmu;
+        let scb = &mut *self.scb;
+
+        sleep_deep(self.syscon, self.wkt, ticks.into().value, || {
+            // Sound, as `sleep_deep` configures PDAWAKECFG to match the
+            // current power configuration.
+            unsafe { pmu.enter_deep_sleep_mode(scb) }
+        });
+    }
+}
+
+/// Power-down mode
+///
+/// Provides a [`Sleep`] implementation for power-down mode and uses the [WKT]
+/// to wake the microcontroller up again, at the right time. In power-down mode,
+/// the flash and most analog blocks are powered down in addition to what is
+/// stopped in deep-sleep mode. Wake-up takes longer than from deep-sleep mode.
+///
+/// Works just like [`sleep::DeepSleep`] otherwise. Please refer to its
+/// documentation for more details.
+///
+/// [`sleep::DeepSleep`]: struct.DeepSleep.html
+pub struct PowerDown<'r, 'clock> {
+    pmu: &'r mut pmu::Handle,
+    scb: &'r mut pac::SCB,
+    syscon: &'r mut syscon::Handle,
+    wkt: &'r mut WKT<init_state::Enabled, &'clock LowPowerClock>,
+}
+
+impl<'r, 'clock> PowerDown<'r, 'clock> {
+    /// Prepare power-down mode
+    ///
+    /// Returns an instance of `sleep::PowerDown`, which implements [`Sleep`]
+    /// and can therefore be used to put the microcontroller to sleep.
+    ///
+    /// Requires references to various peripherals, which will be borrowed for
+    /// as long as the `sleep::PowerDown` instance exists, as they will be
+    /// needed for every call to [`Sleep::sleep`].
+    pub fn prepare(
+        pmu: &'r mut pmu::Handle,
+        scb: &'r mut pac::SCB,
+        syscon: &'r mut syscon::Handle,
+        wkt: &'r mut WKT<init_state::Enabled, &'clock LowPowerClock>,
+    ) -> Self {
+        syscon.enable_interrupt_wakeup::<WktWakeup>();
+
+        PowerDown {
+            pmu,
+            scb,
+            syscon,
+            wkt,
+        }
+    }
+}
+
+impl<'r, 'clock> Sleep<LowPowerClock> for PowerDown<'r, 'clock> {
+    fn sleep<'c, T>(&mut self, ticks: T)
+    where
+        LowPowerClock: 'c,
+        T: Into<Ticks<'c, LowPowerClock>>,
+    {
+        let pmu = &mut *self.pmu;
+        let scb = &mut *self.scb;
+
+        sleep_deep(self.syscon, self.wkt, ticks.into().value, || {
+            // Sound, as `sleep_deep` configures PDAWAKECFG to match the
+            // current power configuration.
+            unsafe { pmu.enter_power_down_mode(scb) }
+        });
+    }
+}
+
+fn sleep_deep<C>(
+    syscon: &mut syscon::Handle,
+    wkt: &mut WKT<init_state::Enabled, C>,
+    ticks: u32,
+    mut enter: impl FnMut(),
+) {
+    // If we try to sleep for zero cycles, we'll never wake up again.
+    if ticks == 0 {
+        return;
+    }
+
+    // See `Regular`'s `Sleep` implementation for an explanation of how
+    // interrupts are handled here.
+    interrupt::free(|_| {
+        let main_clock = syscon.prepare_deep_sleep();
+
+        wkt.start(ticks);
+
+        // Safe, because this is not going to interfere with the critical
+        // section.
+        unsafe { NVIC::unmask(Interrupt::WKT) };
+
+        while let Err(nb::Error::WouldBlock) = wkt.wait() {
+            enter();
+        }
+
+        // If we don't do this, the (possibly non-existing) interrupt handler
+        // will be called as soon as we exit this closure.
+        NVIC::mask(Interrupt::WKT);
+
+        syscon.restore_main_clock(main_clock);
+    });
+}
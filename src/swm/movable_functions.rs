@@ -240,7 +240,7 @@ movable_functions!(
     t0_mat1      , T0_MAT1      , Output, pinassign13, t0_mat1;
     t0_mat2      , T0_MAT2      , Output, pinassign13, t0_mat2;
     t0_mat3      , T0_MAT3      , Output, pinassign14, t0_mat3;
-    t0_cap0      , T0_CAP0      , Output, pinassign14, t0_cap0;
-    t0_cap1      , T0_CAP1      , Output, pinassign14, t0_cap1;
-    t0_cap2      , T0_CAP2      , Output, pinassign14, t0_cap2;
+    t0_cap0      , T0_CAP0      , Input , pinassign14, t0_cap0;
+    t0_cap1      , T0_CAP1      , Input , pinassign14, t0_cap1;
+    t0_cap2      , T0_CAP2      , Input , pinassign14, t0_cap2;
 );
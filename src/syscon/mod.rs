@@ -253,6 +253,113 @@ impl Handle {
     {
         self.starterp1.modify(|_, w| I::disable(w));
     }
+
+    /// Prepare for entering deep-sleep or power-down mode
+    ///
+    /// Selects the IRC/FRO as the main clock, as required for entering those
+    /// modes, and configures PDAWAKECFG to match the current power
+    /// configuration, so all analog blocks are in the same state after
+    /// wake-up. Returns the previous main clock selection, which must be
+    /// passed to [`Handle::restore_main_clock`] after wake-up.
+    pub(crate) fn prepare_deep_sleep(&mut self) -> MainClock {
+        let syscon = syscon();
+
+        let pdruncfg = self.pdruncfg.read().bits();
+        // Sound, as PDAWAKECFG has the same layout as PDRUNCFG.
+        syscon.pdawakecfg.write(|w| unsafe { w.bits(pdruncfg) });
+
+        let main_clock = MainClock::read();
+        MainClock::IOSC.write();
+        main_clock
+    }
+
+    /// Restore the main clock selection after wake-up
+    ///
+    /// See [`Handle::prepare_deep_sleep`].
+    pub(crate) fn restore_main_clock(&mut self, main_clock: MainClock) {
+        // The PLL has been powered up again according to PDAWAKECFG, but it
+        // needs to lock before it can be used. If it is powered down, it will
+        // never lock, and there's no point in waiting.
+        let pll_powered = self.pdruncfg.read().syspll_pd().bit_is_clear();
+        if main_clock.uses_pll() && pll_powered {
+            while syscon().syspllstat.read().lock().bit_is_clear() {}
+        }
+
+        main_clock.write();
+    }
+}
+
+/// The main clock selection
+///
+/// Used to restore the main clock after deep-sleep and power-down modes. See
+/// [`Handle::prepare_deep_sleep`].
+#[derive(Clone, Copy)]
+pub(crate) struct MainClock {
+    sel: u32,
+    #[cfg(feature = "845")]
+    pll_sel: u32,
+}
+
+impl MainClock {
+    /// The IRC/FRO, which is selected after reset
+    const IOSC: Self = Self {
+        sel: 0,
+        #[cfg(feature = "845")]
+        pll_sel: 0,
+    };
+
+    fn read() -> Self {
+        let syscon = syscon();
+
+        Self {
+            sel: syscon.mainclksel.read().bits(),
+            #[cfg(feature = "845")]
+            pll_sel: syscon.mainclkpllsel.read().bits(),
+        }
+    }
+
+    fn write(self) {
+        let syscon = syscon();
+
+        // The new selection only takes effect, once the update enable bit is
+        // toggled. See user manual, section 5.6.
+        //
+        // Sound, as `sel` has either been read from the register, or is the
+        // reset value.
+        syscon.mainclksel.write(|w| unsafe { w.bits(self.sel) });
+        syscon.mainclkuen.write(|w| w.ena().clear_bit());
+        syscon.mainclkuen.write(|w| w.ena().set_bit());
+
+        #[cfg(feature = "845")]
+        {
+            // Sound, as `pll_sel` has either been read from the register, or is
+            // the reset value.
+            syscon
+                .mainclkpllsel
+                .write(|w| unsafe { w.bits(self.pll_sel) });
+            syscon.mainclkplluen.write(|w| w.ena().clear_bit());
+            syscon.mainclkplluen.write(|w| w.ena().set_bit());
+        }
+    }
+
+    #[cfg(feature = "82x")]
+    fn uses_pll(&self) -> bool {
+        // Only 0x3 selects the PLL output. 0x1 selects the PLL input, which
+        // doesn't depend on the PLL being locked.
+        self.sel == 0x3
+    }
+
+    #[cfg(feature = "845")]
+    fn uses_pll(&self) -> bool {
+        self.pll_sel == 0x1
+    }
+}
+
+fn syscon() -> &'static pac::syscon::RegisterBlock {
+    // Sound, as the registers accessed through this are only accessed by
+    // methods of `Handle` that require a mutable reference, and are not
+    // accessed by any other part of the API.
+    unsafe { &*pac::SYSCON::ptr() }
 }
 
 /// Brown-out detection
@@ -670,9 +777,14 @@ impl IoscDerivedClock<init_state::Disabled> {
     }
 }
 
+impl<State> IoscDerivedClock<State> {
+    /// The frequency of the IRC/FRO-derived clock
+    pub(crate) const HZ: u32 = 750_000;
+}
+
 impl<State> clock::Frequency for IoscDerivedClock<State> {
     fn hz(&self) -> u32 {
-        750_000
+        Self::HZ
     }
 }
 
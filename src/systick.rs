@@ -0,0 +1,133 @@
+//! A monotonic clock based on the system timer (SysTick)
+//!
+//! [`SysTickClock`] runs SysTick continuously and counts its overflows in
+//! software, which extends it to 64 bits. It implements
+//! [`embedded_time::Clock`], so it can be used wherever timestamps or timeouts
+//! are needed. At 30 MHz, the maximum core clock frequency, it would take
+//! almost 20,000 years for the tick count to overflow.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use core::cell::RefCell;
+//!
+//! use cortex_m::interrupt::{self, Mutex};
+//! use lpc8xx_hal::{
+//!     embedded_time::{duration::Milliseconds, Clock as _},
+//!     pac::CorePeripherals,
+//!     systick::SysTickClock,
+//! };
+//!
+//! // The core clock runs at 12 MHz, its frequency after reset.
+//! static CLOCK: Mutex<RefCell<Option<SysTickClock<12_000_000>>>> =
+//!     Mutex::new(RefCell::new(None));
+//!
+//! let cp = CorePeripherals::take().unwrap();
+//!
+//! let clock = SysTickClock::new(cp.SYST);
+//! interrupt::free(|cs| CLOCK.borrow(cs).replace(Some(clock)));
+//!
+//! let start = interrupt::free(|cs| {
+//!     CLOCK.borrow(cs).borrow().as_ref().unwrap().try_now().unwrap()
+//! });
+//! let timeout = start + Milliseconds(500_u32);
+//!
+//! // Call this from the SysTick exception handler, for example using the
+//! // `#[exception]` attribute from `cortex-m-rt`.
+//! fn on_systick() {
+//!     interrupt::free(|cs| {
+//!         if let Some(clock) = CLOCK.borrow(cs).borrow_mut().as_mut() {
+//!             clock.on_interrupt();
+//!         }
+//!     });
+//! }
+//! ```
+
+use cortex_m::peripheral::{syst::SystClkSource, SCB};
+use embedded_time::{clock, fraction::Fraction, Instant};
+
+use crate::pac::SYST;
+
+/// The number of ticks per SysTick period, using the maximum reload value
+const PERIOD: u64 = 0x0100_0000;
+
+/// A 64-bit monotonic clock, based on SysTick
+///
+/// `HZ` must be the frequency of the core clock, which SysTick runs on. There
+/// is no default, as the HAL can't know how the core clock has been
+/// configured. After reset, it runs at 12 MHz.
+///
+/// SysTick overflows every 2^24 ticks, which is about every 1.4 seconds at 12
+/// MHz. [`SysTickClock::on_interrupt`] must be called from the SysTick
+/// exception handler, to count those overflows. An overflow that hasn't been
+/// counted yet, for example because the clock is read from a critical section,
+/// is detected and accounted for, as long as it's the only one.
+///
+/// Please refer to the [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct SysTickClock<const HZ: u32> {
+    syst: SYST,
+    overflows: u64,
+}
+
+impl<const HZ: u32> SysTickClock<HZ> {
+    /// Start SysTick and enable its exception
+    pub fn new(mut syst: SYST) -> Self {
+        syst.disable_counter();
+        syst.set_clock_source(SystClkSource::Core);
+        syst.set_reload(PERIOD as u32 - 1);
+        syst.clear_current();
+        syst.enable_interrupt();
+        syst.enable_counter();
+
+        Self { syst, overflows: 0 }
+    }
+
+    /// Count an overflow of SysTick
+    ///
+    /// Must be called from the SysTick exception handler.
+    pub fn on_interrupt(&mut self) {
+        self.overflows += 1;
+    }
+
+    /// Returns the number of ticks since the clock was created
+    pub fn ticks(&self) -> u64 {
+        // An overflow that hasn't been counted yet is indicated by the pending
+        // SysTick exception. Make sure the timer value is consistent with it,
+        // in case an overflow happens while reading.
+        loop {
+            let before = SCB::is_pendst_pending();
+            let value = SYST::get_current() as u64;
+            let after = SCB::is_pendst_pending();
+
+            if before == after {
+                // SysTick pends its exception when the counter reaches zero,
+                // not when it reloads. While the counter is still at zero, the
+                // pending overflow has not happened yet, as far as the tick
+                // count is concerned.
+                let pending = after && value != 0;
+
+                let overflows = self.overflows + pending as u64;
+                return overflows * PERIOD + (PERIOD - 1 - value);
+            }
+        }
+    }
+
+    /// Stop SysTick and release it
+    pub fn free(mut self) -> SYST {
+        self.syst.disable_interrupt();
+        self.syst.disable_counter();
+        self.syst
+    }
+}
+
+impl<const HZ: u32> embedded_time::Clock for SysTickClock<HZ> {
+    type T = u64;
+
+    const SCALING_FACTOR: Fraction = Fraction::new(1, HZ);
+
+    fn try_now(&self) -> Result<Instant<Self>, clock::Error> {
+        Ok(Instant::new(self.ticks()))
+    }
+}
@@ -0,0 +1,561 @@
+//! Software timers, multiplexed onto a single hardware timer
+//!
+//! [`TimerQueue`] manages a fixed number of software timers, each of which can
+//! be one-shot or periodic. It only requires a single hardware timer with an
+//! interrupt, represented by the [`TickSource`] trait, and doesn't allocate.
+//!
+//! [`MrtTickSource`] implements [`TickSource`] using two MRT channels. Since
+//! the queue itself only interacts with the hardware through [`TickSource`],
+//! it can also be driven by a fake tick source, which makes it possible to
+//! test code that uses it on the host.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use lpc8xx_hal::{
+//!     timer_queue::{Mode, MrtTickSource, TimerQueue},
+//!     Peripherals,
+//! };
+//!
+//! let p = Peripherals::take().unwrap();
+//!
+//! let mut syscon = p.SYSCON.split();
+//! let mut mrt = p.MRT0.split(&mut syscon.handle);
+//!
+//! let source = MrtTickSource::new(mrt.mrt0, mrt.mrt1);
+//! let mut queue = TimerQueue::<_, 4>::new(source);
+//!
+//! // The MRT runs at 12 MHz
+//! let blink = queue.start(Mode::Periodic, 6_000_000).unwrap();
+//! let timeout = queue.start(Mode::OneShot, 12_000_000).unwrap();
+//!
+//! loop {
+//!     // This would usually happen in the MRT interrupt handler.
+//!     if mrt.irq.pending().mrt1() {
+//!         queue.on_interrupt(|id| {
+//!             if id == blink {
+//!                 // toggle LED
+//!             }
+//!             if id == timeout {
+//!                 // handle timeout
+//!             }
+//!         });
+//!     }
+//! }
+//! ```
+//!
+//! # Testing on the host
+//!
+//! ```
+//! use lpc8xx_hal::timer_queue::{Mode, TickSource, TimerQueue};
+//!
+//! struct FakeTicks {
+//!     now: u64,
+//!     alarm: u64,
+//! }
+//!
+//! impl TickSource for FakeTicks {
+//!     fn now(&self) -> u64 {
+//!         self.now
+//!     }
+//!
+//!     fn set_alarm(&mut self, delay: u64) {
+//!         self.alarm = self.now.saturating_add(delay);
+//!     }
+//! }
+//!
+//! let source = FakeTicks { now: 0, alarm: 0 };
+//! let mut queue = TimerQueue::<_, 3>::new(source);
+//!
+//! let periodic = queue.start(Mode::Periodic, 10).unwrap();
+//! let one_shot = queue.start(Mode::OneShot, 25).unwrap();
+//! assert_eq!(queue.source().alarm, 10);
+//!
+//! let mut fired = Vec::new();
+//! for _ in 0..3 {
+//!     let alarm = queue.source().alarm;
+//!     queue.source_mut().now = alarm;
+//!     queue.on_interrupt(|id| fired.push((alarm, id)));
+//! }
+//! assert_eq!(fired, [(10, periodic), (20, periodic), (25, one_shot)]);
+//! assert!(!queue.is_active(one_shot));
+//! assert!(queue.is_active(periodic));
+//!
+//! // The timer fell behind. Missed periods are skipped.
+//! queue.source_mut().now = 67;
+//! fired.clear();
+//! queue.on_interrupt(|id| fired.push((67, id)));
+//! assert_eq!(fired, [(67, periodic)]);
+//! assert_eq!(queue.source().alarm, 70);
+//!
+//! assert!(queue.cancel(periodic));
+//! assert!(!queue.cancel(periodic));
+//! assert!(queue.is_empty());
+//! ```
+
+use core::cell::Cell;
+
+use crate::mrt::{self, Channel, Ticks, MAX_VALUE};
+
+/// A source of ticks that can request an interrupt
+///
+/// Implemented by [`MrtTickSource`], and by fake tick sources in tests.
+pub trait TickSource {
+    /// Returns the current time in ticks
+    ///
+    /// The returned value must never decrease.
+    fn now(&self) -> u64;
+
+    /// Request an interrupt after `delay` ticks have passed
+    ///
+    /// Replaces any previously requested interrupt. If `delay` is too long
+    /// for the hardware, the interrupt may be requested earlier. It must not
+    /// be requested later.
+    ///
+    /// [`TimerQueue::on_interrupt`] must be called whenever the requested
+    /// interrupt occurs.
+    fn set_alarm(&mut self, delay: u64);
+}
+
+/// Multiplexes up to `N` software timers onto a single [`TickSource`]
+///
+/// See [module documentation] for more information.
+///
+/// [module documentation]: index.html
+pub struct TimerQueue<S, const N: usize> {
+    source: S,
+    timers: [Option<Timer>; N],
+    next_id: u32,
+}
+
+impl<S, const N: usize> TimerQueue<S, N>
+where
+    S: TickSource,
+{
+    /// Create a timer queue without any active timers
+    pub fn new(mut source: S) -> Self {
+        // Keep the tick source running, even if no timers are active.
+        source.set_alarm(u64::MAX);
+
+        Self {
+            source,
+            timers: [None; N],
+            next_id: 0,
+        }
+    }
+
+    /// Start a timer that expires after `ticks` ticks
+    ///
+    /// A periodic timer expires again every `ticks` ticks, until it is
+    /// canceled. Returns the ID of the new timer, which is passed to the
+    /// closure given to [`TimerQueue::on_interrupt`] when the timer expires.
+    ///
+    /// Returns an error, if `N` timers are already active. `ticks` must not be
+    /// zero.
+    ///
+    /// ```
+    /// use lpc8xx_hal::timer_queue::{Full, Mode, TimerQueue};
+    /// # use lpc8xx_hal::timer_queue::TickSource;
+    /// #
+    /// # struct FakeTicks {
+    /// #     now: u64,
+    /// #     alarm: u64,
+    /// # }
+    /// #
+    /// # impl TickSource for FakeTicks {
+    /// #     fn now(&self) -> u64 {
+    /// #         self.now
+    /// #     }
+    /// #
+    /// #     fn set_alarm(&mut self, delay: u64) {
+    /// #         self.alarm = self.now.saturating_add(delay);
+    /// #     }
+    /// # }
+    /// #
+    /// let source = FakeTicks { now: 0, alarm: 0 };
+    /// let mut queue = TimerQueue::<_, 2>::new(source);
+    ///
+    /// let a = queue.start(Mode::OneShot, 30).unwrap();
+    /// let b = queue.start(Mode::Periodic, 20).unwrap();
+    /// assert_eq!(queue.start(Mode::OneShot, 10), Err(Full));
+    ///
+    /// // The earliest deadline determines the alarm.
+    /// assert_eq!(queue.source().alarm, 20);
+    ///
+    /// // Once a timer has expired, its slot can be used again.
+    /// queue.source_mut().now = 30;
+    /// let mut fired = Vec::new();
+    /// queue.on_interrupt(|id| fired.push(id));
+    /// assert_eq!(fired, [b, a]);
+    /// assert!(queue.start(Mode::OneShot, 10).is_ok());
+    /// ```
+    pub fn start(&mut self, mode: Mode, ticks: u64) -> Result<TimerId, Full> {
+        assert!(ticks != 0);
+
+        let slot = self
+            .timers
+            .iter_mut()
+            .find(|timer| timer.is_none())
+            .ok_or(Full)?;
+
+        let id = TimerId(self.next_id);
+        self.next_id = self.next_id.wrapping_add(1);
+
+        let now = self.source.now();
+        *slot = Some(Timer {
+            id,
+            deadline: now.saturating_add(ticks),
+            period: match mode {
+                Mode::OneShot => None,
+                Mode::Periodic => Some(ticks),
+            },
+        });
+
+        self.set_alarm(now);
+
+        Ok(id)
+    }
+
+    /// Cancel a timer
+    ///
+    /// Returns `false`, if the timer was not active. This is the case, if it
+    /// was a one-shot timer that already expired, or if it had already been
+    /// canceled.
+    ///
+    /// IDs are not reused when a slot becomes free, so the ID of a canceled
+    /// timer can't be mistaken for the ID of a timer that was started later.
+    ///
+    /// ```
+    /// use lpc8xx_hal::timer_queue::{Mode, TimerQueue};
+    /// # use lpc8xx_hal::timer_queue::TickSource;
+    /// #
+    /// # struct FakeTicks {
+    /// #     now: u64,
+    /// #     alarm: u64,
+    /// # }
+    /// #
+    /// # impl TickSource for FakeTicks {
+    /// #     fn now(&self) -> u64 {
+    /// #         self.now
+    /// #     }
+    /// #
+    /// #     fn set_alarm(&mut self, delay: u64) {
+    /// #         self.alarm = self.now.saturating_add(delay);
+    /// #     }
+    /// # }
+    /// #
+    /// let source = FakeTicks { now: 0, alarm: 0 };
+    /// let mut queue = TimerQueue::<_, 1>::new(source);
+    ///
+    /// let old = queue.start(Mode::Periodic, 10).unwrap();
+    /// assert!(queue.cancel(old));
+    ///
+    /// // The new timer gets the same slot, but a different ID.
+    /// let new = queue.start(Mode::Periodic, 10).unwrap();
+    /// assert_ne!(old, new);
+    /// assert!(!queue.is_active(old));
+    /// assert!(!queue.cancel(old));
+    /// assert!(queue.is_active(new));
+    ///
+    /// // The canceled timer is not reported, even though its alarm was
+    /// // still set.
+    /// queue.source_mut().now = 10;
+    /// let mut fired = Vec::new();
+    /// queue.on_interrupt(|id| fired.push(id));
+    /// assert_eq!(fired, [new]);
+    /// ```
+    pub fn cancel(&mut self, id: TimerId) -> bool {
+        for slot in self.timers.iter_mut() {
+            if let Some(timer) = slot {
+                if timer.id == id {
+                    *slot = None;
+                    // The alarm is left as it is. If it was set for this
+                    // timer, the interrupt will not find anything to do.
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Indicates whether the timer with the given ID is active
+    pub fn is_active(&self, id: TimerId) -> bool {
+        self.timers.iter().flatten().any(|timer| timer.id == id)
+    }
+
+    /// Indicates whether no timers are active
+    pub fn is_empty(&self) -> bool {
+        self.timers.iter().all(Option::is_none)
+    }
+
+    /// Returns the deadline of the timer that expires next, in ticks
+    ///
+    /// Returns `None`, if no timers are active.
+    pub fn next_deadline(&self) -> Option<u64> {
+        self.timers
+            .iter()
+            .flatten()
+            .map(|timer| timer.deadline)
+            .min()
+    }
+
+    /// Request the next interrupt from the tick source again
+    ///
+    /// This is only required, if the time of the tick source has been changed
+    /// from the outside, for example using [`MrtTickSource::compensate`].
+    ///
+    /// ```
+    /// use lpc8xx_hal::timer_queue::{Mode, TimerQueue};
+    /// # use lpc8xx_hal::timer_queue::TickSource;
+    /// #
+    /// # struct FakeTicks {
+    /// #     now: u64,
+    /// #     alarm: u64,
+    /// # }
+    /// #
+    /// # impl TickSource for FakeTicks {
+    /// #     fn now(&self) -> u64 {
+    /// #         self.now
+    /// #     }
+    /// #
+    /// #     fn set_alarm(&mut self, delay: u64) {
+    /// #         self.alarm = self.now.saturating_add(delay);
+    /// #     }
+    /// # }
+    /// #
+    /// let source = FakeTicks { now: 0, alarm: 0 };
+    /// let mut queue = TimerQueue::<_, 2>::new(source);
+    ///
+    /// let timer = queue.start(Mode::Periodic, 100).unwrap();
+    /// assert_eq!(queue.source().alarm, 100);
+    ///
+    /// // Time passes without the tick source noticing, e.g. during sleep.
+    /// queue.source_mut().now = 60;
+    /// queue.reschedule();
+    /// assert_eq!(queue.source().alarm, 100);
+    ///
+    /// // If the deadline has already passed, the alarm fires right away.
+    /// queue.source_mut().now = 250;
+    /// queue.reschedule();
+    /// assert_eq!(queue.source().alarm, 251);
+    ///
+    /// let mut fired = Vec::new();
+    /// queue.source_mut().now = 251;
+    /// queue.on_interrupt(|id| fired.push(id));
+    /// assert_eq!(fired, [timer]);
+    /// assert_eq!(queue.next_deadline(), Some(300));
+    /// ```
+    pub fn reschedule(&mut self) {
+        let now = self.source.now();
+        self.set_alarm(now);
+    }
+
+    /// Handle the tick source's interrupt
+    ///
+    /// Calls `f` with the ID of each timer that has expired, then requests
+    /// the next interrupt from the tick source.
+    ///
+    /// Each expired timer is reported once per call. If a periodic timer has
+    /// fallen behind by more than one period, the missed expirations are
+    /// skipped, but its deadlines stay aligned to its period.
+    pub fn on_interrupt(&mut self, mut f: impl FnMut(TimerId)) {
+        let now = self.source.now();
+
+        while let Some(index) = self.next_expired(now) {
+            let slot = &mut self.timers[index];
+            if let Some(timer) = slot {
+                let id = timer.id;
+
+                match timer.period {
+                    Some(period) => {
+                        let behind = now - timer.deadline;
+                        timer.deadline += (behind / period + 1) * period;
+                    }
+                    None => *slot = None,
+                }
+
+                f(id);
+            }
+        }
+
+        self.set_alarm(now);
+    }
+
+    /// Returns a reference to the tick source
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Returns a mutable reference to the tick source
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Release the tick source
+    pub fn free(self) -> S {
+        self.source
+    }
+
+    /// Returns the index of the earliest timer that has expired at `now`
+    fn next_expired(&self, now: u64) -> Option<usize> {
+        self.timers
+            .iter()
+            .enumerate()
+            .filter_map(|(i, timer)| timer.map(|timer| (i, timer.deadline)))
+            .filter(|&(_, deadline)| deadline <= now)
+            .min_by_key(|&(_, deadline)| deadline)
+            .map(|(i, _)| i)
+    }
+
+    fn set_alarm(&mut self, now: u64) {
+        let delay = match self.next_deadline() {
+            Some(deadline) => deadline.saturating_sub(now).max(1),
+            None => u64::MAX,
+        };
+
+        self.source.set_alarm(delay);
+    }
+}
+
+/// Identifies a timer in a [`TimerQueue`]
+///
+/// Returned by [`TimerQueue::start`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct TimerId(u32);
+
+/// The mode of a timer in a [`TimerQueue`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    /// The timer expires once
+    OneShot,
+
+    /// The timer expires repeatedly, until it is canceled
+    Periodic,
+}
+
+/// Returned by [`TimerQueue::start`], if no more timers can be added
+#[derive(Debug, Eq, PartialEq)]
+pub struct Full;
+
+#[derive(Clone, Copy)]
+struct Timer {
+    id: TimerId,
+    deadline: u64,
+    period: Option<u64>,
+}
+
+/// A [`TickSource`] based on two MRT channels
+///
+/// One channel serves as the timebase. It runs continuously in repeat mode,
+/// and its overflows are counted in software, which extends it to 64 bits. The
+/// other channel is used in one-shot mode to generate the alarm interrupt.
+/// Since the timebase never stops, no time is lost between an alarm and setting
+/// the next one.
+///
+/// Ticks are MRT ticks, which means there are 12 million ticks per second, if
+/// the system clock hasn't been changed.
+///
+/// Overflows of the timebase are detected whenever the time is read, which
+/// needs to happen at least once per timebase period of about 179 seconds at
+/// 12 MHz. To make sure of that, the alarm is never set further than half a
+/// period into the future. The alarm interrupt must be handled in time.
+///
+/// The alarm channel's interrupt is enabled by [`MrtTickSource::new`]. Since
+/// all MRT channels share a single interrupt, the interrupt handler should
+/// check [`mrt::Pending::contains`] for the alarm channel, before calling
+/// [`TimerQueue::on_interrupt`]. Setting the next alarm clears the alarm
+/// channel's interrupt flag, if it hasn't been cleared already.
+pub struct MrtTickSource<T: mrt::Trait, A: mrt::Trait> {
+    timebase: Channel<T>,
+    alarm: Channel<A>,
+    overflows: Cell<u64>,
+    last_value: Cell<u32>,
+    offset: u64,
+}
+
+impl<T, A> MrtTickSource<T, A>
+where
+    T: mrt::Trait,
+    A: mrt::Trait,
+{
+    /// Create the tick source
+    ///
+    /// Starts the timebase and enables the interrupt of the alarm channel.
+    pub fn new(mut timebase: Channel<T>, mut alarm: Channel<A>) -> Self {
+        timebase.disable_interrupt();
+        timebase.start(MAX_VALUE);
+        alarm.enable_interrupt();
+
+        Self {
+            timebase,
+            alarm,
+            overflows: Cell::new(0),
+            // Larger than any timer value, so the first read doesn't count as
+            // an overflow.
+            last_value: Cell::new(u32::MAX),
+            offset: 0,
+        }
+    }
+
+    /// Advance the time by the given number of ticks
+    ///
+    /// The MRT doesn't run in deep-sleep and power-down modes. This method can
+    /// be used to account for the time spent in those modes, as measured by
+    /// another timer. Call [`TimerQueue::reschedule`] afterwards, to make sure
+    /// that any timers that expired in the meantime are handled.
+    pub fn compensate(&mut self, ticks: u64) {
+        self.offset += ticks;
+    }
+
+    /// Returns the number of ticks per timebase period
+    ///
+    /// This matches the interval that `Channel::start` configures for
+    /// `MAX_VALUE`.
+    fn period() -> u64 {
+        MAX_VALUE.to_u32() as u64 + 1
+    }
+
+    /// Release the MRT channels
+    pub fn free(mut self) -> (Channel<T>, Channel<A>) {
+        self.alarm.disable_interrupt();
+
+        (self.timebase, self.alarm)
+    }
+}
+
+impl<T, A> TickSource for MrtTickSource<T, A>
+where
+    T: mrt::Trait,
+    A: mrt::Trait,
+{
+    fn now(&self) -> u64 {
+        // The timer counts down, so a value that is larger than the last one
+        // means the timer has wrapped around since then. This doesn't rely on
+        // the interrupt flag, so reading the time never has to modify it.
+        let value = self.timebase.value();
+        if value > self.last_value.get() {
+            self.overflows.set(self.overflows.get() + 1);
+        }
+        self.last_value.set(value);
+
+        let elapsed = self.overflows.get() * Self::period()
+            + (Self::period() - 1 - value as u64);
+
+        self.offset + elapsed
+    }
+
+    fn set_alarm(&mut self, delay: u64) {
+        // Read the time, so overflows keep being tracked, even if no timers
+        // are active.
+        self.now();
+
+        let interval = delay.clamp(1, Self::period() / 2);
+
+        // `start_one_shot` configures an interval of `reload + 1` ticks.
+        //
+        // Sound, as the interval is at most half of `MAX_VALUE + 1`.
+        let reload = unsafe { Ticks::from_u32(interval as u32 - 1) };
+        self.alarm.start_one_shot(reload);
+    }
+}
@@ -30,14 +30,18 @@
 //!
 //! [examples in the repository]: https://github.com/lpc-rs/lpc8xx-hal/tree/master/examples
 
-use embedded_hal::timer;
+use core::marker::PhantomData;
+
+use embedded_hal::timer::{self, CountDown as _};
 use nb;
 use void::Void;
 
 use crate::{
+    clock,
+    delay::{impl_delay, Blocking},
     init_state,
     pac::{self, wkt::ctrl},
-    pmu::LowPowerClock,
+    pmu::{LowPowerClock, WktClkIn},
     syscon::{self, IoscDerivedClock},
 };
 
@@ -46,17 +50,36 @@ use crate::{
 /// Controls the WKT. Use [`Peripherals`] to gain access to an instance of this
 /// struct.
 ///
+/// The type parameter `C` tracks the clock that runs the timer. After
+/// [`WKT::enable`], this is the [`IoscDerivedClock`], which can't be disabled.
+/// When another clock is selected using [`WKT::select_clock`], `C` becomes a
+/// reference to that clock. As long as it is selected, the clock stays
+/// borrowed, which prevents it from being disabled.
+///
+/// The WKT can also be used for blocking delays. The number of ticks is
+/// computed from the frequency of the selected clock. Since the WKT runs on a
+/// slow clock, this is most useful for long delays that don't need to be
+/// precise. A delay restarts the timer, so it can't be used while counting
+/// down.
+///
 /// Please refer to the [module documentation] for more information.
 ///
 /// # `embedded-hal` traits
 /// - [`embedded_hal::timer::CountDown`]
+/// - [`embedded_hal::blocking::delay::DelayUs`]
+/// - [`embedded_hal::blocking::delay::DelayMs`]
 ///
 /// [`Peripherals`]: ../struct.Peripherals.html
 /// [module documentation]: index.html
 /// [`embedded_hal::timer::CountDown`]: #impl-CountDown
-pub struct WKT<State = init_state::Enabled> {
+/// [`embedded_hal::blocking::delay::DelayUs`]: #impl-DelayUs%3Cu32%3E
+/// [`embedded_hal::blocking::delay::DelayMs`]: #impl-DelayMs%3Cu32%3E
+/// [`IoscDerivedClock`]: ../syscon/struct.IoscDerivedClock.html
+pub struct WKT<State = init_state::Enabled, C = IoscDerivedClock> {
     wkt: pac::WKT,
     _state: State,
+    _clock: PhantomData<C>,
+    hz: u32,
 }
 
 impl WKT<init_state::Disabled> {
@@ -64,6 +87,8 @@ impl WKT<init_state::Disabled> {
         WKT {
             wkt,
             _state: init_state::Disabled,
+            _clock: PhantomData,
+            hz: IoscDerivedClock::<init_state::Enabled>::HZ,
         }
     }
 
@@ -87,11 +112,13 @@ impl WKT<init_state::Disabled> {
         WKT {
             wkt: self.wkt,
             _state: init_state::Enabled(()),
+            _clock: PhantomData,
+            hz: IoscDerivedClock::<init_state::Enabled>::HZ,
         }
     }
 }
 
-impl WKT<init_state::Enabled> {
+impl<C> WKT<init_state::Enabled, C> {
     /// Disable the WKT
     ///
     /// This method is only available, if `WKT` is in the [`Enabled`] state.
@@ -99,19 +126,28 @@ impl WKT<init_state::Enabled> {
     /// disabled will not compile.
     ///
     /// Consumes this instance of `WKT` and returns another instance that has
-    /// its `State` type parameter set to [`Disabled`].
+    /// its `State` type parameter set to [`Disabled`]. The selected clock is
+    /// reset to the [`IoscDerivedClock`], which releases any clock that was
+    /// borrowed by [`WKT::select_clock`].
     ///
     /// [`Enabled`]: ../init_state/struct.Enabled.html
     /// [`Disabled`]: ../init_state/struct.Disabled.html
+    /// [`IoscDerivedClock`]: ../syscon/struct.IoscDerivedClock.html
     pub fn disable(
         self,
         syscon: &mut syscon::Handle,
     ) -> WKT<init_state::Disabled> {
+        self.wkt.ctrl.modify(|_, w| {
+            IoscDerivedClock::<init_state::Enabled>::select(w);
+            w
+        });
         syscon.disable_clock(&self.wkt);
 
         WKT {
             wkt: self.wkt,
             _state: init_state::Disabled,
+            _clock: PhantomData,
+            hz: IoscDerivedClock::<init_state::Enabled>::HZ,
         }
     }
 
@@ -122,28 +158,101 @@ impl WKT<init_state::Enabled> {
     ///
     /// All clocks that can run the WKT implement a common trait. Please refer
     /// to [`wkt::Clock`] for a list of clocks that can be passed to this
-    /// method. Selecting an external clock via the WKTCLKIN pin is currently
-    /// not supported.
+    /// method. An external clock can be supplied via the WKTCLKIN pin, using
+    /// [`WktClkIn`].
     ///
-    /// # Limitations
+    /// The clock must be enabled, and it stays borrowed for as long as the
+    /// returned `WKT` instance exists. This makes it impossible to disable the
+    /// clock, while the timer depends on it:
     ///
-    /// Currently, nothing prevents the user from selecting a clock that is
-    /// disabled, attempting to start the timer while the clock is disabled, or
-    /// disabling the clock while the timer is running.
+    /// ``` compile_fail
+    /// use lpc8xx_hal::Peripherals;
+    ///
+    /// let p = Peripherals::take().unwrap();
+    ///
+    /// let mut pmu = p.PMU.split();
+    /// let mut syscon = p.SYSCON.split();
+    ///
+    /// let clock = pmu.low_power_clock.enable(&mut pmu.handle);
+    /// let wkt = p.WKT.enable(&mut syscon.handle).select_clock(&clock);
+    ///
+    /// // Should fail: The clock is still borrowed by the WKT.
+    /// clock.disable(&mut pmu.handle);
+    ///
+    /// drop(wkt);
+    /// ```
     ///
     /// [`wkt::Clock`]: trait.Clock.html
-    pub fn select_clock<C>(&mut self)
+    /// [`WktClkIn`]: ../pmu/struct.WktClkIn.html
+    pub fn select_clock<Clock>(
+        self,
+        clock: &Clock,
+    ) -> WKT<init_state::Enabled, &Clock>
+    where
+        Clock: self::Clock + clock::Enabled + clock::Frequency,
+    {
+        self.wkt.ctrl.modify(|_, w| {
+            Clock::select(w);
+            w
+        });
+
+        WKT {
+            wkt: self.wkt,
+            _state: self._state,
+            _clock: PhantomData,
+            hz: clock.hz(),
+        }
+    }
+
+    /// Returns the frequency of the selected clock
+    pub(crate) fn hz(&self) -> u32 {
+        self.hz
+    }
+
+    /// Returns the current counter value
+    ///
+    /// The counter counts down to zero, once started.
+    pub fn value(&self) -> u32 {
+        self.wkt.count.read().value().bits()
+    }
+
+    /// Indicates whether the counter has reached zero
+    pub fn alarm_occurred(&self) -> bool {
+        self.wkt.ctrl.read().alarmflag().bit_is_set()
+    }
+
+    /// Select a clock for the duration of `f`
+    ///
+    /// Restores the previously selected clock afterwards. The caller must make
+    /// sure that the clock is enabled for the duration of `f`.
+    pub(crate) fn with_clock<Clock, R>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R
     where
-        C: Clock,
+        Clock: self::Clock,
     {
+        let ctrl = self.wkt.ctrl.read();
+        let sel_extclk = ctrl.sel_extclk().bit();
+        let clksel = ctrl.clksel().bit();
+
         self.wkt.ctrl.modify(|_, w| {
-            C::select(w);
+            Clock::select(w);
             w
         });
+
+        let result = f(self);
+
+        self.wkt.ctrl.modify(|_, w| {
+            w.sel_extclk().bit(sel_extclk);
+            w.clksel().bit(clksel)
+        });
+
+        result
     }
 }
 
-impl timer::CountDown for WKT<init_state::Enabled> {
+impl<C> timer::CountDown for WKT<init_state::Enabled, C> {
     type Time = u32;
 
     /// Starts a new count down
@@ -174,7 +283,24 @@ impl timer::CountDown for WKT<init_state::Enabled> {
     }
 }
 
-impl<State> WKT<State> {
+impl<C> Blocking for WKT<init_state::Enabled, C> {
+    fn ticks_per_second(&self) -> u32 {
+        self.hz
+    }
+
+    fn block(&mut self, mut ticks: u64) {
+        while ticks != 0 {
+            let chunk = ticks.min(u32::MAX as u64) as u32;
+            self.start(chunk);
+            while let Err(nb::Error::WouldBlock) = self.wait() {}
+            ticks -= chunk as u64;
+        }
+    }
+}
+
+impl_delay!([C] WKT<init_state::Enabled, C>, []);
+
+impl<State, C> WKT<State, C> {
     /// Return the raw peripheral
     ///
     /// This method serves as an escape hatch from the HAL API. It returns the
@@ -218,6 +344,12 @@ impl<State> Clock for LowPowerClock<State> {
     }
 }
 
+impl Clock for WktClkIn {
+    fn select(w: &mut ctrl::W) {
+        w.sel_extclk().external();
+    }
+}
+
 #[cfg(feature = "82x")]
 mod target {
     pub fn select_internal_oscillator(w: &mut crate::pac::wkt::ctrl::W) {